@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use jsonpath::{map_each, map_each_mut, set, set_mut, MapAction};
+use serde_json::{json, Value};
+
+// Roughly 100 bytes per item, the largest document serializes to about 10MB.
+fn document(items: usize) -> Value {
+    json!({
+        "counter": 0,
+        "items": (0..items)
+            .map(|i| json!({"id": i, "name": format!("item-{i}"), "tags": ["a", "b", "c"]}))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+fn incr(v: &Value) -> MapAction<Value> {
+    match v.as_i64() {
+        Some(n) => MapAction::ReplaceWith(json!(n + 1)),
+        None => MapAction::Keep,
+    }
+}
+
+fn small_updates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small_update");
+    for items in [1_000, 10_000, 100_000] {
+        let doc = document(items);
+        group.bench_with_input(BenchmarkId::new("map_each", items), &doc, |b, doc| {
+            b.iter(|| map_each("$.counter", doc, &mut incr).unwrap())
+        });
+        let mut doc = doc;
+        group.bench_function(BenchmarkId::new("map_each_mut", items), |b| {
+            b.iter(|| map_each_mut("$.counter", &mut doc, &mut |v: &mut Value| incr(v)).unwrap())
+        });
+        let doc = doc;
+        group.bench_with_input(BenchmarkId::new("set", items), &doc, |b, doc| {
+            b.iter(|| set("$.items[0].name", doc, &json!("x")).unwrap())
+        });
+        let mut doc = doc;
+        group.bench_function(BenchmarkId::new("set_mut", items), |b| {
+            b.iter(|| set_mut("$.items[0].name", &mut doc, &json!("x")).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, small_updates);
+criterion_main!(benches);
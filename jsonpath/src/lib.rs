@@ -5,9 +5,12 @@ extern crate pest_derive;
 extern crate pest;
 
 pub mod parser;
+pub mod pointer;
 
-use serde_json::Value;
+use serde_json::{Map, Number, Value};
 
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::rc::Rc;
 
 pub fn get<'a>(path: &str, val: &'a Value) -> Result<Vec<&'a Value>, String> {
@@ -44,155 +47,496 @@ pub fn get<'a>(path: &str, val: &'a Value) -> Result<Vec<&'a Value>, String> {
     Ok(res)
 }
 
-// For the mutating commands note that parents match before their children.
-// To avoid setting a value in the parent we abort when we see that the prefix
-// of a path was already mutated since that invalidates the subsequent match.
+// For the mutating commands note that parents can match before their children.
+// To avoid setting a value in the parent we skip paths when we see that one of their
+// prefixes was already replaced or deleted since that invalidates the subsequent match.
 
 pub fn set(path: &str, val: &Value, to: &Value) -> Result<Value, String> {
+    let mut res = val.clone();
+    set_mut(path, &mut res, to)?;
+    Ok(res)
+}
+
+// Returns the number of values that were set.
+pub fn set_mut(path: &str, val: &mut Value, to: &Value) -> Result<usize, String> {
     let mut selectors = parser::parse(path)?;
-    if selectors == vec![parser::Selector::Root] {
-        return Ok(to.clone());
-    }
 
     // When the last element is a DotMemberName selector we also set it. To do this
     // we need to match to the second to last element and if we did so and this
     // element is an object we add/update that key.
-    let maybe_last_selector = match selectors.last().unwrap() {
-        parser::Selector::DotMemberName(_) => selectors.pop(),
-        _ => None,
-    };
-
-    let mut res = val.clone();
-    if selectors == vec![parser::Selector::Root] {
-        if let Some(parser::Selector::DotMemberName(k)) = &maybe_last_selector {
-            res.as_object_mut()
-                .and_then(|obj| obj.insert(k.clone(), to.clone()));
+    match selectors.last() {
+        Some(parser::Selector::DotMemberName(k)) => {
+            let k = k.clone();
+            selectors.pop();
+            map_each_selector_mut(selectors, val, false, &mut |_, v: &mut Value| match v {
+                Value::Object(object) => {
+                    object.insert(k.clone(), to.clone());
+                    MapAction::Mutated
+                }
+                _ => MapAction::Keep,
+            })
         }
-        return Ok(res);
+        _ => map_each_selector_mut(selectors, val, false, &mut |_, _| {
+            MapAction::ReplaceWith(to.clone())
+        }),
     }
+}
 
-    let paths = matches(selectors, val)?;
+// Matches the path the way set_mut does without changing anything, so it fails exactly when
+// set_mut would.
+pub fn check_set(path: &str, val: &Value) -> Result<(), String> {
+    let mut selectors = parser::parse(path)?;
+    if let Some(parser::Selector::DotMemberName(_)) = selectors.last() {
+        selectors.pop();
+    }
+    matches(selectors, val).map(|_| ())
+}
 
-    let mut prefix = vec![];
-    paths.into_iter().for_each(|path| {
-        if !prefix.is_empty() && path.starts_with(&prefix) {
-            return;
-        }
-        prefix = path.clone();
-        let mut segments = path.into_iter().peekable();
-        let mut cur = &mut res;
-        while let Some(segment) = segments.next() {
-            cur = match segment {
-                PathSegment::MemberName(k) => {
-                    if let Some(object) = cur.as_object_mut() {
-                        if segments.peek().is_none() {
-                            if let Some(parser::Selector::DotMemberName(nk)) = &maybe_last_selector
-                            {
-                                object.get_mut(&k).and_then(|nested| {
-                                    nested
-                                        .as_object_mut()
-                                        .and_then(|matched| matched.insert(nk.clone(), to.clone()))
-                                });
-                                break;
-                            } else {
-                                object.insert(k.clone(), to.clone());
-                            }
-                        }
-                        object.get_mut(&k).unwrap()
-                    } else {
-                        break;
-                    }
+// Like set but missing objects along the path are created, which requires a definite path, i.e.
+// one that can match at most a single value. Existing values are never replaced by objects,
+// indices have to be in bounds and JSON Pointer tokens are array indices only for arrays.
+pub fn set_create(path: &str, val: &Value, to: &Value) -> Result<Value, String> {
+    let mut res = val.clone();
+    set_create_mut(path, &mut res, to)?;
+    Ok(res)
+}
+
+pub fn set_create_mut(path: &str, val: &mut Value, to: &Value) -> Result<(), String> {
+    let selectors = parser::parse(path)?;
+
+    // Resolve the whole path before creating anything so that errors leave the document as is,
+    // cur becomes None once we are past the existing values.
+    let mut segments = vec![];
+    let mut cur = Some(&*val);
+    for selector in selectors.iter().skip(1) {
+        let segment = match selector {
+            parser::Selector::DotMemberName(k) => create_member(cur, k)?,
+            parser::Selector::ArrayIndex(i) => element(cur, *i)?,
+            parser::Selector::Union(members) => match members.as_slice() {
+                [parser::UnionMember::MemberName(_), parser::UnionMember::ArrayIndex(i)]
+                    if cur.is_some_and(Value::is_array) =>
+                {
+                    element(cur, *i)?
                 }
-                PathSegment::ArrayIndex(i) => {
-                    if let Some(array) = cur.as_array_mut() {
-                        if segments.peek().is_none() {
-                            if let Some(parser::Selector::DotMemberName(nk)) = &maybe_last_selector
-                            {
-                                array.get_mut(i).and_then(|nested| {
-                                    nested
-                                        .as_object_mut()
-                                        .and_then(|matched| matched.insert(nk.clone(), to.clone()))
-                                });
-                            } else {
-                                array.insert(i, to.clone());
-                            }
-                            break;
-                        }
-                        array.get_mut(i).unwrap()
-                    } else {
-                        break;
-                    }
+                [parser::UnionMember::MemberName(k), parser::UnionMember::ArrayIndex(_)] => {
+                    create_member(cur, k)?
                 }
-            };
-        }
+                _ => return Err("unable to create values along an indefinite path".to_owned()),
+            },
+            _ => return Err("unable to create values along an indefinite path".to_owned()),
+        };
+        cur = cur.and_then(|v| match &segment {
+            PathSegment::MemberName(k) => v.get(k),
+            PathSegment::ArrayIndex(i) => v.get(i),
+        });
+        segments.push(segment);
+    }
+
+    let cur = segments.iter().fold(val, |cur, segment| match segment {
+        PathSegment::MemberName(k) => cur
+            .as_object_mut()
+            .unwrap()
+            .entry(k.clone())
+            .or_insert_with(|| Value::Object(Map::new())),
+        PathSegment::ArrayIndex(i) => cur.get_mut(i).unwrap(),
     });
-    Ok(res)
+    *cur = to.clone();
+    Ok(())
+}
+
+fn create_member(val: Option<&Value>, k: &str) -> Result<PathSegment, String> {
+    match val {
+        None | Some(Value::Object(_)) => Ok(PathSegment::MemberName(k.to_owned())),
+        _ => Err(format!(
+            "unable to create '{k}' in a value that is not an object"
+        )),
+    }
+}
+
+fn element(val: Option<&Value>, i: isize) -> Result<PathSegment, String> {
+    match val {
+        Some(Value::Array(array)) => match wrapped_index(i, array.len()) {
+            Some(i) => Ok(PathSegment::ArrayIndex(i)),
+            None => Err("index out of bounds".to_owned()),
+        },
+        _ => Err("unable to index a value that is not an array".to_owned()),
+    }
 }
 
 pub enum MapAction<T> {
     ReplaceWith(T),
     Delete,
+    Keep,
+    // The value was changed in place, later matches below it are still visited.
+    Mutated,
 }
 
+// The root can only be replaced, callers that want to delete it have to do so themselves.
 pub fn map_each(
     path: &str,
     val: &Value,
     fun: &mut dyn FnMut(&Value) -> MapAction<Value>,
 ) -> Result<Value, String> {
+    let mut res = val.clone();
+    map_each_mut(path, &mut res, &mut |v: &mut Value| fun(v))?;
+    Ok(res)
+}
+
+// Like map_each but the document is changed in place, which avoids copying it for small updates.
+// If an error is returned the document was left untouched, otherwise the number of values that
+// were replaced, deleted or mutated.
+pub fn map_each_mut(
+    path: &str,
+    val: &mut Value,
+    fun: &mut dyn FnMut(&mut Value) -> MapAction<Value>,
+) -> Result<usize, String> {
     let selectors = parser::parse(path)?;
-    let paths = matches(selectors, val)?;
+    map_each_selector_mut(selectors, val, false, &mut |_, v| fun(v))
+}
+
+// Like map_each_mut but the matches are visited deepest first, i.e. children before their parents
+// and later array elements before earlier ones, so that inserting or removing array elements
+// doesn't move the matches that are still to be visited. The position of the match is passed
+// along so that replies can be put back into match order.
+pub fn map_each_deepest_first_mut(
+    path: &str,
+    val: &mut Value,
+    fun: &mut dyn FnMut(usize, &mut Value) -> MapAction<Value>,
+) -> Result<usize, String> {
+    let selectors = parser::parse(path)?;
+    map_each_selector_mut(selectors, val, true, fun)
+}
 
+fn map_each_selector_mut(
+    selectors: Vec<parser::Selector>,
+    val: &mut Value,
+    deepest_first: bool,
+    fun: &mut dyn FnMut(usize, &mut Value) -> MapAction<Value>,
+) -> Result<usize, String> {
+    let mut paths: Vec<_> = matches(selectors, val)?.into_iter().enumerate().collect();
+    if deepest_first {
+        // paths sort after their prefixes, the sort is stable so repeated matches keep their order
+        paths.sort_by(|(_, a), (_, b)| b.cmp(a));
+    }
+
+    // Unions can match the same value more than once, it is only visited the first time. A
+    // replaced or deleted value invalidates the paths below it, so those are skipped for every
+    // such path and not just the last one since recursive selectors don't match in document order.
+    let mut visited = BTreeSet::new();
+    let mut replaced = BTreeSet::new();
+    let mut deleted = vec![];
+    let mut changed = 0;
+    for (pos, path) in paths {
+        if !visited.insert(path.clone()) || (0..=path.len()).any(|i| replaced.contains(&path[..i]))
+        {
+            continue;
+        }
+        let cur = match find_mut(val, &path) {
+            Some(v) => v,
+            None => continue,
+        };
+        match fun(pos, cur) {
+            MapAction::ReplaceWith(v) => {
+                *cur = v;
+                replaced.insert(path);
+            }
+            MapAction::Delete => {
+                deleted.push(path.clone());
+                replaced.insert(path);
+            }
+            MapAction::Mutated => {}
+            MapAction::Keep => continue,
+        }
+        changed += 1;
+    }
+
+    // Deleting in reverse order keeps the array indices of the remaining paths valid.
+    deleted.sort();
+    deleted.dedup();
+    for path in deleted.iter().rev() {
+        let (last, parent) = match path.split_last() {
+            Some(v) => v,
+            None => continue,
+        };
+        match (find_mut(val, parent), last) {
+            (Some(Value::Object(object)), PathSegment::MemberName(k)) => {
+                object.shift_remove(k);
+            }
+            (Some(Value::Array(array)), PathSegment::ArrayIndex(i)) if *i < array.len() => {
+                array.remove(*i);
+            }
+            _ => {}
+        }
+    }
+    Ok(changed)
+}
+
+/// Apply a JSON Merge Patch (RFC 7396) to every match of the path. When the path ends in a
+/// member name the patch is merged into the parent as '{"name": patch}' so that missing members
+/// are created. A null patch deletes every matched member from its parent, since the root and
+/// array elements can't be deleted that way matching either of them is an error.
+pub fn merge(path: &str, val: &Value, patch: &Value) -> Result<Value, String> {
     let mut res = val.clone();
-    let mut prefix = vec![];
-    paths.iter().for_each(|path| {
-        if !prefix.is_empty() && path.starts_with(&prefix) {
+    merge_mut(path, &mut res, patch)?;
+    Ok(res)
+}
+
+// Returns the number of values the patch was merged into.
+pub fn merge_mut(path: &str, val: &mut Value, patch: &Value) -> Result<usize, String> {
+    let mut selectors = parser::parse(path)?;
+    if patch.is_null() {
+        for path in matches(selectors.clone(), val)? {
+            match path.last() {
+                Some(PathSegment::MemberName(_)) => {}
+                Some(PathSegment::ArrayIndex(_)) => {
+                    return Err("unable to delete an array element with a null patch".to_owned())
+                }
+                None => return Err("unable to delete the root with a null patch".to_owned()),
+            }
+        }
+        return map_each_selector_mut(selectors, val, false, &mut |_, _| MapAction::Delete);
+    }
+    let (patch, wrapped) = match selectors.last() {
+        Some(parser::Selector::DotMemberName(k)) => {
+            let mut object = Map::new();
+            object.insert(k.clone(), patch.clone());
+            selectors.pop();
+            (Value::Object(object), true)
+        }
+        _ => (patch.clone(), false),
+    };
+    map_each_selector_mut(selectors, val, false, &mut |_, v: &mut Value| {
+        if wrapped && !v.is_object() {
+            return MapAction::Keep;
+        }
+        merge_patch(v, &patch);
+        MapAction::Mutated
+    })
+}
+
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match patch.as_object() {
+        Some(object) => object,
+        None => {
+            *target = patch.clone();
             return;
         }
-        prefix = path.clone();
-        let mut segments = path.iter().peekable();
-        let mut cur = Some(&mut res);
-        while let Some(segment) = segments.next() {
-            if cur.is_none() {
-                break;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let object = target.as_object_mut().unwrap();
+    for (k, v) in patch {
+        if v.is_null() {
+            object.shift_remove(k);
+        } else {
+            merge_patch(object.entry(k.clone()).or_insert(Value::Null), v);
+        }
+    }
+}
+
+/// Apply a JSON Patch (RFC 6902) to the document. The operations are applied in order to a copy
+/// of the document, if any of them fails the whole patch is rejected.
+pub fn patch(val: &Value, operations: &Value) -> Result<Value, String> {
+    let mut res = val.clone();
+    patch_mut(&mut res, operations)?;
+    Ok(res)
+}
+
+// Like patch but the document is changed in place. Every change is recorded so that it can be
+// undone when a later operation fails, which leaves the document as it was. Returns the number
+// of changes, which is zero for patches only testing values.
+pub fn patch_mut(val: &mut Value, operations: &Value) -> Result<usize, String> {
+    let operations = match operations.as_array() {
+        Some(v) => v,
+        None => return Err("json patch must be an array of operations".to_owned()),
+    };
+    let mut undo = vec![];
+    for operation in operations {
+        if let Err(e) = patch_operation(val, operation, &mut undo) {
+            undo.into_iter().rev().for_each(|change| change.revert(val));
+            return Err(e);
+        }
+    }
+    Ok(undo.len())
+}
+
+// The inverse of a change made by a patch operation.
+enum Undo {
+    // Remove the value added at the path.
+    Remove(Vec<PathSegment>),
+    // Put back the value removed from the path, at its former position among the members of an
+    // object or elements of an array.
+    Insert(Vec<PathSegment>, usize, Value),
+    // Put back the value replaced at the path.
+    Replace(Vec<PathSegment>, Value),
+}
+
+impl Undo {
+    fn revert(self, doc: &mut Value) {
+        match self {
+            Undo::Remove(path) => {
+                remove_at(doc, &path);
             }
-            cur = match segment {
-                PathSegment::MemberName(k) => {
-                    let object = cur.unwrap().as_object_mut().unwrap();
-                    if segments.peek().is_none() {
-                        match fun(object.get(&k.clone()).unwrap()) {
-                            MapAction::Delete => {
-                                object.remove(&k.clone());
-                            }
-                            MapAction::ReplaceWith(v) => {
-                                object.insert(k.clone(), v);
-                            }
-                        }
-                        break;
+            Undo::Insert(mut path, at, v) => {
+                let last = path.pop().unwrap();
+                match (value_at_mut(doc, &path), last) {
+                    (Value::Object(object), PathSegment::MemberName(k)) => {
+                        let mut members = std::mem::take(object).into_iter().collect::<Vec<_>>();
+                        members.insert(at, (k, v));
+                        *object = members.into_iter().collect();
                     }
-                    object.get_mut(&k.clone())
-                }
-                PathSegment::ArrayIndex(i) => {
-                    let array = cur.unwrap().as_array_mut().unwrap();
-                    if segments.peek().is_none() {
-                        match fun(array.get(*i).unwrap()) {
-                            MapAction::Delete => {
-                                array.remove(*i);
-                            }
-                            MapAction::ReplaceWith(v) => {
-                                array.insert(*i, v);
-                            }
-                        }
-                        break;
-                    }
-                    array.get_mut(*i)
+                    (Value::Array(array), PathSegment::ArrayIndex(_)) => array.insert(at, v),
+                    _ => unreachable!(),
                 }
+            }
+            Undo::Replace(path, v) => *value_at_mut(doc, &path) = v,
+        }
+    }
+}
+
+fn patch_operation(doc: &mut Value, operation: &Value, undo: &mut Vec<Undo>) -> Result<(), String> {
+    let member = |name: &str| -> Result<&Value, String> {
+        match operation.get(name) {
+            Some(v) => Ok(v),
+            None => Err(format!(
+                "json patch operation is missing '{name}': {operation}"
+            )),
+        }
+    };
+    let pointer = |name: &str| -> Result<Vec<String>, String> {
+        match member(name)?.as_str() {
+            Some(v) => pointer::tokens(v),
+            None => Err(format!(
+                "json patch operation '{name}' is not a string: {operation}"
+            )),
+        }
+    };
+
+    match member("op")?.as_str() {
+        Some("add") => patch_add(doc, &pointer("path")?, member("value")?.clone(), undo),
+        Some("remove") => {
+            let (path, at, v) = patch_remove(doc, &pointer("path")?)?;
+            undo.push(Undo::Insert(path, at, v));
+            Ok(())
+        }
+        Some("replace") => {
+            let path = pointer::resolve_tokens(&pointer("path")?, doc)?;
+            let v = member("value")?.clone();
+            let old = std::mem::replace(value_at_mut(doc, &path), v);
+            undo.push(Undo::Replace(path, old));
+            Ok(())
+        }
+        Some("move") => {
+            let from = pointer("from")?;
+            let path = pointer("path")?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                return Err(format!(
+                    "can not move a value into one of its children: {operation}"
+                ));
+            }
+            let (from, at, v) = patch_remove(doc, &from)?;
+            // the value ends up elsewhere in the document, undoing the move needs its own copy
+            undo.push(Undo::Insert(from, at, v.clone()));
+            patch_add(doc, &path, v, undo)
+        }
+        Some("copy") => {
+            let from = pointer::resolve_tokens(&pointer("from")?, doc)?;
+            let v = value_at_mut(doc, &from).clone();
+            patch_add(doc, &pointer("path")?, v, undo)
+        }
+        Some("test") => {
+            let path = pointer::resolve_tokens(&pointer("path")?, doc)?;
+            if value_at_mut(doc, &path) != member("value")? {
+                return Err(format!("json patch test failed: {operation}"));
+            }
+            Ok(())
+        }
+        _ => Err(format!("invalid json patch operation: {operation}")),
+    }
+}
+
+fn patch_add(
+    doc: &mut Value,
+    tokens: &[String],
+    v: Value,
+    undo: &mut Vec<Undo>,
+) -> Result<(), String> {
+    let (last, parent) = match tokens.split_last() {
+        Some(v) => v,
+        None => {
+            let old = std::mem::replace(doc, v);
+            undo.push(Undo::Replace(vec![], old));
+            return Ok(());
+        }
+    };
+    let mut path = pointer::resolve_tokens(parent, doc)?;
+    match value_at_mut(doc, &path) {
+        Value::Object(object) => {
+            let old = object.insert(last.clone(), v);
+            path.push(PathSegment::MemberName(last.clone()));
+            undo.push(match old {
+                Some(old) => Undo::Replace(path, old),
+                None => Undo::Remove(path),
+            });
+        }
+        Value::Array(array) => {
+            let idx = match last.as_str() {
+                "-" => array.len(),
+                _ => pointer::array_index(last)?,
             };
+            if idx > array.len() {
+                return Err(format!("array index out of bounds: {last}"));
+            }
+            array.insert(idx, v);
+            path.push(PathSegment::ArrayIndex(idx));
+            undo.push(Undo::Remove(path));
         }
-    });
-    Ok(res)
+        _ => return Err(format!("can not add to a scalar value: {last}")),
+    };
+    Ok(())
+}
+
+// Returns the resolved path of the removed value, its position in the parent and the value.
+fn patch_remove(
+    doc: &mut Value,
+    tokens: &[String],
+) -> Result<(Vec<PathSegment>, usize, Value), String> {
+    let path = pointer::resolve_tokens(tokens, doc)?;
+    if path.is_empty() {
+        return Err("can not remove the root".to_owned());
+    }
+    let (at, v) = remove_at(doc, &path);
+    Ok((path, at, v))
+}
+
+// The path has to be resolved against the document beforehand and must not be the root.
+fn remove_at(doc: &mut Value, path: &[PathSegment]) -> (usize, Value) {
+    let (last, parent) = path.split_last().unwrap();
+    match (value_at_mut(doc, parent), last) {
+        (Value::Object(object), PathSegment::MemberName(k)) => {
+            let at = object.keys().position(|key| key == k).unwrap();
+            (at, object.shift_remove(k).unwrap())
+        }
+        (Value::Array(array), PathSegment::ArrayIndex(i)) => (*i, array.remove(*i)),
+        _ => unreachable!(),
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+// The path has to be resolved against the document beforehand.
+fn value_at_mut<'a>(val: &'a mut Value, path: &[PathSegment]) -> &'a mut Value {
+    find_mut(val, path).unwrap()
+}
+
+// Paths matched before the document was changed might no longer exist.
+fn find_mut<'a>(val: &'a mut Value, path: &[PathSegment]) -> Option<&'a mut Value> {
+    path.iter().try_fold(val, |cur, segment| match segment {
+        PathSegment::MemberName(k) => cur.get_mut(k),
+        PathSegment::ArrayIndex(i) => cur.get_mut(i),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PathSegment {
     MemberName(String),
     ArrayIndex(usize),
@@ -212,26 +556,49 @@ macro_rules! cons {
 // Protection against abusive selectors
 const MAX_ACCUMULATOR_SIZE: usize = 1000;
 
+type ConsPath = Rc<ConsList<PathSegment>>;
+
 pub fn matches(
     selectors: Vec<parser::Selector>,
     val: &Value,
 ) -> Result<Vec<Vec<PathSegment>>, String> {
-    type ConsPath = Rc<ConsList<PathSegment>>;
+    select(&selectors, val, val).map(|paths| {
+        paths
+            .into_iter()
+            .map(|(cons_path, _)| {
+                // TODO: this is a performance bottleneck for large result sets, find a better datastructure
+                let mut cur = &cons_path;
+                let mut res = vec![];
+                while let ConsList::Cons(head, tail) = cur.as_ref() {
+                    cur = tail;
+                    res.push(head.clone());
+                }
+                res.into_iter().rev().collect()
+            })
+            .collect()
+    })
+}
 
+/// Walk the selectors starting at 'start', the 'root' is only needed to resolve absolute paths
+/// inside of filter expressions.
+fn select<'a>(
+    selectors: &'a [parser::Selector],
+    root: &'a Value,
+    start: &'a Value,
+) -> Result<Vec<(ConsPath, &'a Value)>, String> {
     selectors
-        .into_iter()
-        .fold(Ok(vec![]), |acc, node| {
-            let acc: Vec<(ConsPath, &Value)> = acc?;
+        .iter()
+        .try_fold(vec![], |acc: Vec<(ConsPath, &Value)>, node| {
             if acc.len() > MAX_ACCUMULATOR_SIZE {
                 return Err(format!("too many passthrough matches ({})", acc.len()));
             };
             Ok(match node {
-                parser::Selector::Root => vec![(Rc::new(ConsList::Nil), val)],
+                parser::Selector::Root => vec![(Rc::new(ConsList::Nil), start)],
                 parser::Selector::DotMemberName(k) => acc
                     .into_iter()
                     .filter_map(|(p, v)| {
                         v.as_object()
-                            .and_then(|object| object.get(&k))
+                            .and_then(|object| object.get(k))
                             .map(|val| (cons!(p, PathSegment::MemberName(k.clone())), val))
                     })
                     .collect(),
@@ -239,7 +606,7 @@ pub fn matches(
                     .into_iter()
                     .filter_map(|(p, v)| {
                         v.as_array().and_then(|array| {
-                            wrapped_index(i, array.len()).map(|safe_index| {
+                            wrapped_index(*i, array.len()).map(|safe_index| {
                                 let elem = unsafe { array.get_unchecked(safe_index) };
                                 (cons!(p, PathSegment::ArrayIndex(safe_index)), elem)
                             })
@@ -274,7 +641,7 @@ pub fn matches(
                                 cur.extend(object.into_iter().map(|(key, val)| {
                                     (cons!(pp, PathSegment::MemberName(key.clone())), val)
                                 }));
-                                if let Some(val) = object.get(&k.clone()) {
+                                if let Some(val) = object.get(k) {
                                     let key = k.clone();
                                     col.push((cons!(pp, PathSegment::MemberName(key)), val));
                                 };
@@ -303,7 +670,7 @@ pub fn matches(
                                 cur.extend(array.iter().enumerate().map(|(idx, val)| {
                                     (cons!(pp, PathSegment::ArrayIndex(idx)), val)
                                 }));
-                                if let Some(safe_index) = wrapped_index(i, array.len()) {
+                                if let Some(safe_index) = wrapped_index(*i, array.len()) {
                                     let elem = unsafe { array.get_unchecked(safe_index) };
                                     col.push((
                                         cons!(pp, PathSegment::ArrayIndex(safe_index)),
@@ -341,6 +708,33 @@ pub fn matches(
                         col
                     })
                     .collect(),
+                parser::Selector::DecendantFilter(expr) => {
+                    let mut col: Vec<(ConsPath, &Value)> = vec![];
+                    for (p, v) in acc {
+                        let mut cur: Vec<(ConsPath, &Value)> = vec![(p, v)];
+                        while let Some((pp, next)) = cur.pop() {
+                            if let Some(object) = next.as_object() {
+                                for (key, val) in object {
+                                    let path = cons!(pp, PathSegment::MemberName(key.clone()));
+                                    if filter_matches(expr, root, val)? {
+                                        col.push((Rc::clone(&path), val));
+                                    }
+                                    cur.push((path, val));
+                                }
+                            }
+                            if let Some(array) = next.as_array() {
+                                for (idx, val) in array.iter().enumerate() {
+                                    let path = cons!(pp, PathSegment::ArrayIndex(idx));
+                                    if filter_matches(expr, root, val)? {
+                                        col.push((Rc::clone(&path), val));
+                                    }
+                                    cur.push((path, val));
+                                }
+                            }
+                        }
+                    }
+                    col
+                }
                 parser::Selector::Union(union_elements) => acc
                     .into_iter()
                     .flat_map(|(p, v)| {
@@ -405,25 +799,131 @@ pub fn matches(
                         col
                     })
                     .collect(),
-            })
-        })
-        .map(|paths| {
-            paths
-                .into_iter()
-                .map(|(cons_path, _)| {
-                    // TODO: this is a performance bottleneck for large result sets, find a better datastructure
-                    let mut cur = &cons_path;
-                    let mut res = vec![];
-                    while let ConsList::Cons(head, tail) = cur.as_ref() {
-                        cur = tail;
-                        res.push(head.clone());
+                parser::Selector::Filter(expr) => {
+                    let mut col: Vec<(ConsPath, &Value)> = vec![];
+                    for (p, v) in acc {
+                        if let Some(object) = v.as_object() {
+                            for (key, val) in object {
+                                if filter_matches(expr, root, val)? {
+                                    col.push((cons!(p, PathSegment::MemberName(key.clone())), val));
+                                }
+                            }
+                        }
+                        if let Some(array) = v.as_array() {
+                            for (idx, val) in array.iter().enumerate() {
+                                if filter_matches(expr, root, val)? {
+                                    col.push((cons!(p, PathSegment::ArrayIndex(idx)), val));
+                                }
+                            }
+                        }
                     }
-                    res.into_iter().rev().collect()
-                })
-                .collect()
+                    col
+                }
+            })
         })
 }
 
+fn filter_matches(
+    expr: &parser::FilterExpression,
+    root: &Value,
+    cur: &Value,
+) -> Result<bool, String> {
+    Ok(match expr {
+        parser::FilterExpression::Or(exprs) => {
+            for e in exprs {
+                if filter_matches(e, root, cur)? {
+                    return Ok(true);
+                }
+            }
+            false
+        }
+        parser::FilterExpression::And(exprs) => {
+            for e in exprs {
+                if !filter_matches(e, root, cur)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        parser::FilterExpression::Not(e) => !filter_matches(e, root, cur)?,
+        parser::FilterExpression::Exists(path) => !filter_path_values(path, root, cur)?.is_empty(),
+        parser::FilterExpression::Comparison(lhs, op, rhs) => {
+            let lhs = comparable_values(lhs, root, cur)?;
+            let rhs = comparable_values(rhs, root, cur)?;
+            compare(&lhs, op, &rhs)
+        }
+    })
+}
+
+fn filter_path_values<'a>(
+    path: &'a parser::FilterPath,
+    root: &'a Value,
+    cur: &'a Value,
+) -> Result<Vec<&'a Value>, String> {
+    let nodes = match path {
+        parser::FilterPath::Relative(selectors) => select(selectors, root, cur)?,
+        parser::FilterPath::Absolute(selectors) => select(selectors, root, root)?,
+    };
+    Ok(nodes.into_iter().map(|(_, v)| v).collect())
+}
+
+fn comparable_values<'a>(
+    comparable: &'a parser::Comparable,
+    root: &'a Value,
+    cur: &'a Value,
+) -> Result<Vec<&'a Value>, String> {
+    match comparable {
+        parser::Comparable::Literal(v) => Ok(vec![v]),
+        parser::Comparable::Path(path) => filter_path_values(path, root, cur),
+    }
+}
+
+// Empty sides are treated like in the spec, i.e. they are only equal to each other. For paths
+// that select more then one value the comparison holds if it holds for any pair of values.
+fn compare(lhs: &[&Value], op: &parser::ComparisonOperator, rhs: &[&Value]) -> bool {
+    use parser::ComparisonOperator::*;
+
+    match (lhs.is_empty(), rhs.is_empty()) {
+        (true, true) => matches!(op, Eq | Le | Ge),
+        (true, false) | (false, true) => matches!(op, Ne),
+        (false, false) => lhs.iter().any(|l| {
+            rhs.iter().any(|r| match op {
+                Eq => values_equal(l, r),
+                Ne => !values_equal(l, r),
+                Lt => values_less(l, r),
+                Le => values_less(l, r) || values_equal(l, r),
+                Gt => values_less(r, l),
+                Ge => values_less(r, l) || values_equal(l, r),
+            })
+        }),
+    }
+}
+
+fn values_equal(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Number(l), Value::Number(r)) => number_ordering(l, r) == Some(Ordering::Equal),
+        _ => lhs == rhs,
+    }
+}
+
+fn values_less(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Number(l), Value::Number(r)) => number_ordering(l, r) == Some(Ordering::Less),
+        (Value::String(l), Value::String(r)) => l < r,
+        _ => false,
+    }
+}
+
+fn number_ordering(lhs: &Number, rhs: &Number) -> Option<Ordering> {
+    if let (Some(l), Some(r)) = (lhs.as_i64(), rhs.as_i64()) {
+        return Some(l.cmp(&r));
+    }
+    if let (Some(l), Some(r)) = (lhs.as_u64(), rhs.as_u64()) {
+        return Some(l.cmp(&r));
+    }
+    lhs.as_f64()?.partial_cmp(&rhs.as_f64()?)
+}
+
 fn normalize_slice_bound(i: isize, len: isize) -> isize {
     if i >= 0 {
         i
@@ -450,7 +950,7 @@ fn wrapped_index(idx: isize, len: usize) -> Option<usize> {
         return None;
     }
     if idx >= 0 {
-        Some(idx as usize)
+        Some(idx as usize).filter(|i| *i < len)
     } else {
         let positive_idx = idx.checked_add_unsigned(len);
         positive_idx.map(|i| i as usize)
@@ -636,6 +1136,13 @@ mod tests {
                     expect: vec![&json!("2"), &json!("1")],
                 }],
             },
+            Test {
+                input: json!({"a": {"price": 5}, "b": [{"price": 1}, {"price": 20}]}),
+                expectations: vec![Expectation {
+                    path: "$..[?(@.price < 10)]",
+                    expect: vec![&json!({"price": 5}), &json!({"price": 1})],
+                }],
+            },
             Test {
                 input: json!({
                     "a": {
@@ -662,6 +1169,67 @@ mod tests {
                     },
                 ],
             },
+            Test {
+                input: json!({
+                    "limit": 10,
+                    "items": [
+                        {"name": "a", "price": 5},
+                        {"name": "b", "price": 10.5, "tags": ["x"]},
+                        {"name": "c", "price": 15},
+                        {"name": "d"}
+                    ]
+                }),
+                expectations: vec![
+                    Expectation {
+                        path: "$.items[?(@.price < 10)].name",
+                        expect: vec![&json!("a")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.price >= 10.5)].name",
+                        expect: vec![&json!("b"), &json!("c")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.price == 5.0)].name",
+                        expect: vec![&json!("a")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.price != 5)].name",
+                        expect: vec![&json!("b"), &json!("c"), &json!("d")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.price > $.limit)].name",
+                        expect: vec![&json!("b"), &json!("c")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.tags)].name",
+                        expect: vec![&json!("b")],
+                    },
+                    Expectation {
+                        path: "$.items[?(!@.price)].name",
+                        expect: vec![&json!("d")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.name == 'a' || @.name == \"c\")].price",
+                        expect: vec![&json!(5), &json!(15)],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.price > 1 && (@.name < 'b' || @.tags[0] == 'x'))].name",
+                        expect: vec![&json!("a"), &json!("b")],
+                    },
+                    Expectation {
+                        path: "$.items[?(@.price == 'a')]",
+                        expect: vec![],
+                    },
+                    Expectation {
+                        path: "$[?(@ == 10)]",
+                        expect: vec![&json!(10)],
+                    },
+                    Expectation {
+                        path: "$..[?(@.price < 10)].name",
+                        expect: vec![&json!("a")],
+                    },
+                ],
+            },
         ]
         .iter()
         .for_each(|test| {
@@ -727,6 +1295,18 @@ mod tests {
                         path: "$[-1]",
                         expect: json!([1, 2]),
                     },
+                    Expectation {
+                        path: "$[0,2]",
+                        expect: json!([2]),
+                    },
+                    Expectation {
+                        path: "$[0,1,0]",
+                        expect: json!([3]),
+                    },
+                    Expectation {
+                        path: "$[*]",
+                        expect: json!([]),
+                    },
                 ],
             },
             Test {
@@ -765,6 +1345,17 @@ mod tests {
                     },
                 ],
             },
+            Test {
+                input: json!({
+                    "c": 1,
+                    "a": 2,
+                    "b": 3
+                }),
+                expectations: vec![Expectation {
+                    path: "$.a",
+                    expect: json!({"c": 1, "b": 3}),
+                }],
+            },
             Test {
                 input: json!({
                     "a": 1,
@@ -779,16 +1370,427 @@ mod tests {
             },
         ]
         .iter()
+        .for_each(|test| {
+            test.expectations.iter().for_each(|e| {
+                let res =
+                    map_each(e.path, &test.input, &mut |_| MapAction::Delete).expect("error del");
+                // comparing objects ignores the key order, so compare them serialized
+                assert_eq!(res.to_string(), e.expect.to_string())
+            });
+        });
+    }
+
+    #[test]
+    fn map_each_success_tests() {
+        fn append_one(v: &Value) -> MapAction<Value> {
+            match v.as_array() {
+                Some(array) => {
+                    let mut array = array.clone();
+                    array.push(json!(1));
+                    MapAction::ReplaceWith(Value::Array(array))
+                }
+                None => MapAction::Keep,
+            }
+        }
+
+        struct Expectation<'a> {
+            path: &'a str,
+            expect: Value,
+        }
+        struct Test<'a> {
+            input: Value,
+            expectations: Vec<Expectation<'a>>,
+        }
+        [
+            Test {
+                input: json!([]),
+                expectations: vec![Expectation {
+                    path: "$",
+                    expect: json!([1]),
+                }],
+            },
+            Test {
+                input: json!({"a": [], "b": {"a": [0]}, "c": "d"}),
+                expectations: vec![
+                    Expectation {
+                        path: "$..a",
+                        expect: json!({"a": [1], "b": {"a": [0, 1]}, "c": "d"}),
+                    },
+                    Expectation {
+                        path: "$.*",
+                        expect: json!({"a": [1], "b": {"a": [0]}, "c": "d"}),
+                    },
+                ],
+            },
+            Test {
+                input: json!([[], "a", [[]]]),
+                expectations: vec![
+                    Expectation {
+                        path: "$[*]",
+                        expect: json!([[1], "a", [[], 1]]),
+                    },
+                    Expectation {
+                        path: "$..*",
+                        expect: json!([[1], "a", [[], 1]]),
+                    },
+                ],
+            },
+        ]
+        .iter()
         .for_each(|test| {
             test.expectations.iter().for_each(|e| {
                 assert_eq!(
-                    map_each(e.path, &test.input, &mut |_| MapAction::Delete).expect("error del"),
+                    map_each(e.path, &test.input, &mut append_one).expect("error map_each"),
                     e.expect
                 )
             });
         });
     }
 
+    #[test]
+    fn merge_success_tests() {
+        struct Test<'a> {
+            input: Value,
+            path: &'a str,
+            patch: Value,
+            expect: Value,
+        }
+        [
+            // examples from RFC 7396 appendix A
+            Test {
+                input: json!({"a": "b"}),
+                path: "$",
+                patch: json!({"a": "c"}),
+                expect: json!({"a": "c"}),
+            },
+            Test {
+                input: json!({"a": "b"}),
+                path: "$",
+                patch: json!({"b": "c"}),
+                expect: json!({"a": "b", "b": "c"}),
+            },
+            Test {
+                input: json!({"a": "b"}),
+                path: "$",
+                patch: json!({"a": null}),
+                expect: json!({}),
+            },
+            Test {
+                input: json!({"a": "b", "b": "c"}),
+                path: "$",
+                patch: json!({"a": null}),
+                expect: json!({"b": "c"}),
+            },
+            Test {
+                input: json!({"a": ["b"]}),
+                path: "$",
+                patch: json!({"a": "c"}),
+                expect: json!({"a": "c"}),
+            },
+            Test {
+                input: json!({"a": "c"}),
+                path: "$",
+                patch: json!({"a": ["b"]}),
+                expect: json!({"a": ["b"]}),
+            },
+            Test {
+                input: json!({"a": {"b": "c"}}),
+                path: "$",
+                patch: json!({"a": {"b": "d", "c": null}}),
+                expect: json!({"a": {"b": "d"}}),
+            },
+            Test {
+                input: json!({"a": [{"b": "c"}]}),
+                path: "$",
+                patch: json!({"a": [1]}),
+                expect: json!({"a": [1]}),
+            },
+            Test {
+                input: json!(["a", "b"]),
+                path: "$",
+                patch: json!(["c", "d"]),
+                expect: json!(["c", "d"]),
+            },
+            Test {
+                input: json!({"a": "b"}),
+                path: "$",
+                patch: json!(["c"]),
+                expect: json!(["c"]),
+            },
+            Test {
+                input: json!({"e": null}),
+                path: "$",
+                patch: json!({"a": 1}),
+                expect: json!({"e": null, "a": 1}),
+            },
+            Test {
+                input: json!([1, 2]),
+                path: "$",
+                patch: json!({"a": "b", "c": null}),
+                expect: json!({"a": "b"}),
+            },
+            Test {
+                input: json!({}),
+                path: "$",
+                patch: json!({"a": {"bb": {"ccc": null}}}),
+                expect: json!({"a": {"bb": {}}}),
+            },
+            // merging at paths
+            Test {
+                input: json!({"a": {"b": 1, "c": 2}}),
+                path: "$.a",
+                patch: json!({"b": null, "d": 3}),
+                expect: json!({"a": {"c": 2, "d": 3}}),
+            },
+            Test {
+                input: json!({"a": 1}),
+                path: "$.b",
+                patch: json!({"c": 2, "d": null}),
+                expect: json!({"a": 1, "b": {"c": 2}}),
+            },
+            Test {
+                input: json!({"a": 1, "b": 2}),
+                path: "$.a",
+                patch: json!(null),
+                expect: json!({"b": 2}),
+            },
+            Test {
+                input: json!({"a": 1, "b": {"a": 2, "c": 3}}),
+                path: "$..a",
+                patch: json!(null),
+                expect: json!({"b": {"c": 3}}),
+            },
+            Test {
+                input: json!({"a": 1, "b": 2}),
+                path: "$['a']",
+                patch: json!(null),
+                expect: json!({"b": 2}),
+            },
+            Test {
+                input: json!({"a": 1, "b": {"c": 2}}),
+                path: "$.*",
+                patch: json!(null),
+                expect: json!({}),
+            },
+            Test {
+                input: json!({"a": 1}),
+                path: "$.b",
+                patch: json!(null),
+                expect: json!({"a": 1}),
+            },
+            Test {
+                input: json!({"x": {"a": 1}, "y": {"a": 2}, "z": 3}),
+                path: "$.*.a",
+                patch: json!(4),
+                expect: json!({"x": {"a": 4}, "y": {"a": 4}, "z": 3}),
+            },
+            Test {
+                input: json!([{"a": 1}, 2]),
+                path: "$[*]",
+                patch: json!({"b": 3}),
+                expect: json!([{"a": 1, "b": 3}, {"b": 3}]),
+            },
+        ]
+        .iter()
+        .for_each(|test| {
+            let res = merge(test.path, &test.input, &test.patch).expect("error merge");
+            // comparing objects ignores the key order, so compare them serialized
+            assert_eq!(res.to_string(), test.expect.to_string())
+        });
+    }
+
+    #[test]
+    fn merge_failure_tests() {
+        [
+            (json!({"a": 1}), "$"),
+            (Value::Null, "$"),
+            (json!({"arr": [1, 2]}), "$.arr[0]"),
+            (json!({"arr": [1, 2]}), "$.arr[*]"),
+            (json!({"a": 1, "arr": [1, 2]}), "$..*"),
+        ]
+        .iter()
+        .for_each(|(input, path)| {
+            let mut doc = input.clone();
+            assert!(merge_mut(path, &mut doc, &Value::Null).is_err(), "{path}");
+            assert_eq!(&doc, input, "{path}");
+        });
+    }
+
+    #[test]
+    fn patch_success_tests() {
+        struct Test {
+            input: Value,
+            patch: Value,
+            expect: Value,
+        }
+        // examples from RFC 6902 appendix A
+        [
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "add", "path": "/baz", "value": "qux"}]),
+                expect: json!({"foo": "bar", "baz": "qux"}),
+            },
+            Test {
+                input: json!({"foo": ["bar", "baz"]}),
+                patch: json!([{"op": "add", "path": "/foo/1", "value": "qux"}]),
+                expect: json!({"foo": ["bar", "qux", "baz"]}),
+            },
+            Test {
+                input: json!({"baz": "qux", "foo": "bar"}),
+                patch: json!([{"op": "remove", "path": "/baz"}]),
+                expect: json!({"foo": "bar"}),
+            },
+            Test {
+                input: json!({"foo": ["bar", "qux", "baz"]}),
+                patch: json!([{"op": "remove", "path": "/foo/1"}]),
+                expect: json!({"foo": ["bar", "baz"]}),
+            },
+            Test {
+                input: json!({"baz": "qux", "foo": "bar"}),
+                patch: json!([{"op": "replace", "path": "/baz", "value": "boo"}]),
+                expect: json!({"baz": "boo", "foo": "bar"}),
+            },
+            Test {
+                input: json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+                patch: json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+                expect: json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+            },
+            Test {
+                input: json!({"foo": ["all", "grass", "cows", "eat"]}),
+                patch: json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]),
+                expect: json!({"foo": ["all", "cows", "eat", "grass"]}),
+            },
+            Test {
+                input: json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+                patch: json!([
+                    {"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2}
+                ]),
+                expect: json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+            },
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]),
+                expect: json!({"foo": "bar", "child": {"grandchild": {}}}),
+            },
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([
+                    {"op": "add", "path": "/baz", "value": "qux", "xyz": 123}
+                ]),
+                expect: json!({"foo": "bar", "baz": "qux"}),
+            },
+            Test {
+                input: json!({"/": 9, "~1": 10}),
+                patch: json!([{"op": "test", "path": "/~01", "value": 10}]),
+                expect: json!({"/": 9, "~1": 10}),
+            },
+            Test {
+                input: json!({"foo": ["bar"]}),
+                patch: json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]),
+                expect: json!({"foo": ["bar", ["abc", "def"]]}),
+            },
+            // additional edge cases
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "replace", "path": "", "value": [1]}]),
+                expect: json!([1]),
+            },
+            Test {
+                input: json!({"foo": {"bar": 1}}),
+                patch: json!([
+                    {"op": "copy", "from": "/foo", "path": "/baz"},
+                    {"op": "replace", "path": "/baz/bar", "value": 2}
+                ]),
+                expect: json!({"foo": {"bar": 1}, "baz": {"bar": 2}}),
+            },
+        ]
+        .iter()
+        .for_each(|test| {
+            let res = patch(&test.input, &test.patch).expect("error patch");
+            // comparing objects ignores the key order, so compare them serialized
+            assert_eq!(res.to_string(), test.expect.to_string())
+        });
+    }
+
+    #[test]
+    fn patch_failure_tests() {
+        struct Test {
+            input: Value,
+            patch: Value,
+        }
+        [
+            // examples from RFC 6902 appendix A
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]),
+            },
+            Test {
+                input: json!({"baz": "qux"}),
+                patch: json!([{"op": "test", "path": "/baz", "value": "bar"}]),
+            },
+            Test {
+                input: json!({"/": 9, "~1": 10}),
+                patch: json!([{"op": "test", "path": "/~01", "value": "10"}]),
+            },
+            // additional edge cases
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!({"op": "remove", "path": "/foo"}),
+            },
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "frobnicate", "path": "/foo"}]),
+            },
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "replace", "path": "/foo"}]),
+            },
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([{"op": "remove", "path": "/baz"}]),
+            },
+            Test {
+                input: json!({"foo": [1]}),
+                patch: json!([{"op": "add", "path": "/foo/2", "value": 2}]),
+            },
+            Test {
+                input: json!({"foo": {"bar": {}}}),
+                patch: json!([{"op": "move", "from": "/foo", "path": "/foo/bar/baz"}]),
+            },
+            Test {
+                input: json!({"foo": "bar"}),
+                patch: json!([
+                    {"op": "remove", "path": "/foo"},
+                    {"op": "test", "path": "/foo", "value": "bar"}
+                ]),
+            },
+            Test {
+                input: json!({"a": 1, "b": [1, 2], "c": {"d": 2, "e": 3}}),
+                patch: json!([
+                    {"op": "remove", "path": "/a"},
+                    {"op": "add", "path": "/b/0", "value": 0},
+                    {"op": "replace", "path": "/c/d", "value": 4},
+                    {"op": "move", "from": "/b/2", "path": "/c/d"},
+                    {"op": "move", "from": "/c/e", "path": "/f"},
+                    {"op": "copy", "from": "/c", "path": "/a"},
+                    {"op": "add", "path": "", "value": {"g": 5}},
+                    {"op": "test", "path": "/g", "value": 6}
+                ]),
+            },
+        ]
+        .iter()
+        .for_each(|test| {
+            patch(&test.input, &test.patch)
+                .expect_err(&format!("expected error applying {}", test.patch));
+            // changes of the operations before the failing one are undone in place
+            let mut doc = test.input.clone();
+            patch_mut(&mut doc, &test.patch)
+                .expect_err(&format!("expected error applying {}", test.patch));
+            assert_eq!(doc.to_string(), test.input.to_string());
+        });
+    }
+
     #[test]
     fn set_success_tests() {
         struct Expectation<'a> {
@@ -838,6 +1840,31 @@ mod tests {
                     },
                 ],
             },
+            Test {
+                input: json!({"a": [1, 2], "b": {}}),
+                expectations: vec![
+                    Expectation {
+                        path: "$.a[1]",
+                        set_to: json!("x"),
+                        expect: json!({"a": [1, "x"], "b": {}}),
+                    },
+                    Expectation {
+                        path: "/a/0",
+                        set_to: json!("x"),
+                        expect: json!({"a": ["x", 2], "b": {}}),
+                    },
+                    Expectation {
+                        path: "/b/c",
+                        set_to: json!("x"),
+                        expect: json!({"a": [1, 2], "b": {"c": "x"}}),
+                    },
+                    Expectation {
+                        path: "/a/2",
+                        set_to: json!("x"),
+                        expect: json!({"a": [1, 2], "b": {}}),
+                    },
+                ],
+            },
             Test {
                 input: json!({
                     "a": {"b":{"c": 1}},
@@ -889,4 +1916,175 @@ mod tests {
             });
         });
     }
+
+    #[test]
+    fn set_create_success_tests() {
+        struct Test<'a> {
+            input: Value,
+            path: &'a str,
+            set_to: Value,
+            expect: Value,
+        }
+        [
+            Test {
+                input: json!({}),
+                path: "$.a.b.c",
+                set_to: json!(1),
+                expect: json!({"a": {"b": {"c": 1}}}),
+            },
+            Test {
+                input: json!({"a": {"d": 2}}),
+                path: "a.b",
+                set_to: json!(1),
+                expect: json!({"a": {"d": 2, "b": 1}}),
+            },
+            Test {
+                input: json!({"a": [{}, {"c": 3}]}),
+                path: "$.a[-1].b",
+                set_to: json!(1),
+                expect: json!({"a": [{}, {"c": 3, "b": 1}]}),
+            },
+            Test {
+                input: json!({"a": [1, 2]}),
+                path: "/a/0",
+                set_to: json!("x"),
+                expect: json!({"a": ["x", 2]}),
+            },
+            Test {
+                input: json!({}),
+                path: "/a/0",
+                set_to: json!("x"),
+                expect: json!({"a": {"0": "x"}}),
+            },
+            Test {
+                input: json!({"a": 1}),
+                path: "$",
+                set_to: json!(2),
+                expect: json!(2),
+            },
+        ]
+        .iter()
+        .for_each(|test| {
+            assert_eq!(
+                set_create(test.path, &test.input, &test.set_to).expect("error set_create"),
+                test.expect,
+                "{}",
+                test.path
+            )
+        });
+    }
+
+    #[test]
+    fn set_create_failure_tests() {
+        [
+            (json!({}), "$..a"),
+            (json!({}), "$.*.a"),
+            (json!({"a": [1]}), "$.a[*]"),
+            (json!({"a": [1]}), r#"$.a["b","c"]"#),
+            (json!({"a": [1]}), "$.a[1]"),
+            (json!({"a": [1]}), "$.a.b"),
+            (json!({"a": 1}), "$.a.b"),
+            (json!({}), "$[0]"),
+            (json!({}), "$.a.b[0]"),
+            (json!({"a": {}}), "$.a.b.c[0]"),
+        ]
+        .iter()
+        .for_each(|(input, path)| {
+            let mut doc = input.clone();
+            assert!(set_create_mut(path, &mut doc, &json!(1)).is_err(), "{path}");
+            assert_eq!(&doc, input, "{path}");
+        });
+    }
+
+    #[test]
+    fn map_each_mut_tests() {
+        let mut input = json!({"a": [1, [2]], "b": {"a": [3]}});
+        let mut lengths = vec![];
+        map_each_mut(
+            "$..a",
+            &mut input,
+            &mut |v: &mut Value| match v.as_array_mut() {
+                Some(array) => {
+                    array.push(json!(0));
+                    lengths.push(array.len());
+                    MapAction::Mutated
+                }
+                None => MapAction::Keep,
+            },
+        )
+        .expect("error map_each_mut");
+        assert_eq!(input, json!({"a": [1, [2], 0], "b": {"a": [3, 0]}}));
+        assert_eq!(lengths, vec![3, 2]);
+
+        let mut input = json!({"a": [1, 2], "b": "c"});
+        map_each_mut(
+            "$.*[*]",
+            &mut input,
+            &mut |v: &mut Value| match v.as_i64() {
+                Some(1) => MapAction::Delete,
+                Some(n) => MapAction::ReplaceWith(json!(n * 10)),
+                None => MapAction::Keep,
+            },
+        )
+        .expect("error map_each_mut");
+        assert_eq!(input, json!({"a": [20], "b": "c"}));
+
+        // values below mutated values are still visited
+        let mut input = json!({"a": [{"a": [1]}]});
+        let mut lengths = vec![];
+        map_each_mut(
+            "$..a",
+            &mut input,
+            &mut |v: &mut Value| match v.as_array_mut() {
+                Some(array) => {
+                    array.push(json!(9));
+                    lengths.push(array.len());
+                    MapAction::Mutated
+                }
+                None => MapAction::Keep,
+            },
+        )
+        .expect("error map_each_mut");
+        assert_eq!(input, json!({"a": [{"a": [1, 9]}, 9]}));
+        assert_eq!(lengths, vec![2, 2]);
+
+        // the matches of recursive selectors are not in document order, visiting them deepest
+        // first keeps the paths valid when array elements are inserted
+        let mut input = json!({"x": [[1, 2], [3, 4]], "y": [5, 6]});
+        let mut visited = vec![];
+        let changed =
+            map_each_deepest_first_mut("$..*", &mut input, &mut |i, v: &mut Value| match v
+                .as_array_mut()
+            {
+                Some(array) => {
+                    visited.push(i);
+                    array.insert(0, json!([]));
+                    MapAction::Mutated
+                }
+                None => MapAction::Keep,
+            })
+            .expect("error map_each_deepest_first_mut");
+        assert_eq!(
+            input,
+            json!({"x": [[], [[], 1, 2], [[], 3, 4]], "y": [[], 5, 6]})
+        );
+        assert_eq!(visited.len(), 4);
+        assert_eq!(changed, 4);
+
+        // values matched more than once by a union are only visited once
+        let mut input = json!([1, 2]);
+        map_each_mut(
+            "$[0,1,0]",
+            &mut input,
+            &mut |v: &mut Value| match v.as_i64() {
+                Some(n) => {
+                    *v = json!(n + 1);
+                    MapAction::Mutated
+                }
+                None => MapAction::Keep,
+            },
+        )
+        .expect("error map_each_mut");
+        assert_eq!(input, json!([2, 3]));
+    }
 }
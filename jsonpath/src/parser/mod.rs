@@ -3,13 +3,15 @@ use pest::{
     Parser,
 };
 
+use serde_json::Value;
+
 use std::str::FromStr;
 
 #[derive(Parser)]
 #[grammar = "parser/jsonpath.pest"]
 struct JSONPathParser;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Selector {
     Root,
     DotMemberName(String),
@@ -19,7 +21,9 @@ pub enum Selector {
     DecendantDotMemberName(String),
     DecendantWildcard,
     DecendantArrayIndex(isize),
+    DecendantFilter(FilterExpression),
     Union(Vec<UnionMember>),
+    Filter(FilterExpression),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -28,7 +32,60 @@ pub enum UnionMember {
     ArrayIndex(isize),
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FilterExpression {
+    Or(Vec<FilterExpression>),
+    And(Vec<FilterExpression>),
+    Not(Box<FilterExpression>),
+    Exists(FilterPath),
+    Comparison(Comparable, ComparisonOperator, Comparable),
+}
+
+/// Paths inside of filters start with a Root selector; for relative paths it stands for the
+/// current node '@' and for absolute paths for the root node '$' of the document.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FilterPath {
+    Relative(Vec<Selector>),
+    Absolute(Vec<Selector>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Comparable {
+    Literal(Value),
+    Path(FilterPath),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ComparisonOperator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Legacy paths are the paths of RedisJSON v1, which do not start with a '$'. Callers use the
+/// kind to decide between the reply semantics of v1 and v2, JSON Pointers behave like JSONPath.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PathKind {
+    JSONPath,
+    Legacy,
+}
+
+pub fn kind(source: &str) -> PathKind {
+    if source.starts_with('$') || source.starts_with('/') {
+        PathKind::JSONPath
+    } else {
+        PathKind::Legacy
+    }
+}
+
+// Paths starting with a '/' are JSON Pointers, everything else is JSONPath.
 pub fn parse(source: &str) -> Result<Vec<Selector>, String> {
+    if source.starts_with('/') {
+        return crate::pointer::selectors(source);
+    }
     let pairs = match JSONPathParser::parse(Rule::jsonpath, source) {
         Ok(v) => v,
         Err(e) => return Err(format!("unable to parse: {e}")),
@@ -52,7 +109,8 @@ macro_rules! inner {
 
 fn parse_pair(pair: Pair<Rule>) -> Result<Selector, String> {
     match pair.as_rule() {
-        Rule::root => Ok(Selector::Root),
+        Rule::root | Rule::legacy_root => Ok(Selector::Root),
+        Rule::dot_member_name => parse_dot_selector(pair),
         Rule::selector => inner!(pair, parse_pair),
         Rule::dot_selector => inner!(pair, parse_dot_selector),
         Rule::dot_wildcard_selector => Ok(Selector::Wildcard),
@@ -90,6 +148,7 @@ fn parse_decendant_selector(pair: Pair<Rule>) -> Result<Selector, String> {
         Rule::dot_member_name => parse_dot_selector(pair),
         Rule::index_selector => inner!(pair, parse_index_selector),
         Rule::wildcard | Rule::index_wildcard_selector => Ok(Selector::Wildcard),
+        Rule::filter_selector => inner!(pair, parse_filter_selector),
         _ => unreachable!(),
     }?;
     // convert to decendant variant
@@ -97,14 +156,11 @@ fn parse_decendant_selector(pair: Pair<Rule>) -> Result<Selector, String> {
         Selector::DotMemberName(m) => Selector::DecendantDotMemberName(m),
         Selector::ArrayIndex(i) => Selector::DecendantArrayIndex(i),
         Selector::Wildcard => Selector::DecendantWildcard,
+        Selector::Filter(expr) => Selector::DecendantFilter(expr),
         _ => unreachable!(),
     })
 }
 
-fn unimplemented<T>(pair: Pair<Rule>) -> Result<T, String> {
-    Err(format!("unimplemented: {:}", pair.as_str()))
-}
-
 fn parse_union_selector(pair: Pair<Rule>) -> Result<Selector, String> {
     let cur = pair.into_inner();
     let mut children = vec![];
@@ -147,7 +203,92 @@ fn parse_array_slice_selector(pair: Pair<Rule>) -> Result<Selector, String> {
 }
 
 fn parse_filter_selector(pair: Pair<Rule>) -> Result<Selector, String> {
-    unimplemented(pair)
+    Ok(Selector::Filter(parse_filter_expression(pair)?))
+}
+
+fn parse_filter_expression(pair: Pair<Rule>) -> Result<FilterExpression, String> {
+    match pair.as_rule() {
+        Rule::logical_or_expr => {
+            let mut children = pair
+                .into_inner()
+                .map(parse_filter_expression)
+                .collect::<Result<Vec<FilterExpression>, String>>()?;
+            if children.len() == 1 {
+                return Ok(children.pop().unwrap());
+            }
+            Ok(FilterExpression::Or(children))
+        }
+        Rule::logical_and_expr => {
+            let mut children = pair
+                .into_inner()
+                .map(parse_filter_expression)
+                .collect::<Result<Vec<FilterExpression>, String>>()?;
+            if children.len() == 1 {
+                return Ok(children.pop().unwrap());
+            }
+            Ok(FilterExpression::And(children))
+        }
+        Rule::paren_expr => inner!(pair, parse_filter_expression),
+        Rule::not_expr => {
+            let negated = inner!(pair, parse_filter_expression)?;
+            Ok(FilterExpression::Not(Box::new(negated)))
+        }
+        Rule::test_expr => {
+            let path = inner!(pair, parse_filter_path)?;
+            Ok(FilterExpression::Exists(path))
+        }
+        Rule::comparison_expr => {
+            let mut inner = pair.into_inner();
+            let lhs = parse_comparable(inner.next().unwrap())?;
+            let op = match inner.next().unwrap().as_str() {
+                "==" => ComparisonOperator::Eq,
+                "!=" => ComparisonOperator::Ne,
+                "<" => ComparisonOperator::Lt,
+                "<=" => ComparisonOperator::Le,
+                ">" => ComparisonOperator::Gt,
+                ">=" => ComparisonOperator::Ge,
+                _ => unreachable!(),
+            };
+            let rhs = parse_comparable(inner.next().unwrap())?;
+            Ok(FilterExpression::Comparison(lhs, op, rhs))
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn parse_comparable(pair: Pair<Rule>) -> Result<Comparable, String> {
+    match pair.as_rule() {
+        Rule::literal => Ok(Comparable::Literal(inner!(pair, parse_literal)?)),
+        Rule::filter_path => Ok(Comparable::Path(parse_filter_path(pair)?)),
+        _ => unreachable!(),
+    }
+}
+
+fn parse_filter_path(pair: Pair<Rule>) -> Result<FilterPath, String> {
+    let mut inner = pair.into_inner();
+    let relative = inner.next().unwrap().as_str() == "@";
+    let mut selectors = vec![Selector::Root];
+    for next in inner {
+        selectors.push(parse_pair(next)?);
+    }
+    if relative {
+        Ok(FilterPath::Relative(selectors))
+    } else {
+        Ok(FilterPath::Absolute(selectors))
+    }
+}
+
+fn parse_literal(pair: Pair<Rule>) -> Result<Value, String> {
+    match pair.as_rule() {
+        Rule::number => match serde_json::from_str::<Value>(pair.as_str()) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("invalid number {}: {e}", pair.as_str())),
+        },
+        Rule::quoted_member_name => Ok(Value::String(member_name_from_quoted(pair)?)),
+        Rule::boolean => Ok(Value::Bool(pair.as_str() == "true")),
+        Rule::null => Ok(Value::Null),
+        _ => unreachable!(),
+    }
 }
 
 fn member_name_from_quoted(pair: Pair<Rule>) -> Result<String, String> {
@@ -229,6 +370,26 @@ mod tests {
                 input: ".a",
                 expect: vec![Selector::Root, Selector::DotMemberName("a".to_owned())],
             },
+            Test {
+                input: ".",
+                expect: vec![Selector::Root],
+            },
+            Test {
+                input: "a.b[0]",
+                expect: vec![
+                    Selector::Root,
+                    Selector::DotMemberName("a".to_owned()),
+                    Selector::DotMemberName("b".to_owned()),
+                    Selector::ArrayIndex(0),
+                ],
+            },
+            Test {
+                input: "..a",
+                expect: vec![
+                    Selector::Root,
+                    Selector::DecendantDotMemberName("a".to_owned()),
+                ],
+            },
             Test {
                 input: "$.ab",
                 expect: vec![Selector::Root, Selector::DotMemberName("ab".to_owned())],
@@ -353,6 +514,109 @@ mod tests {
                 input: r"$[-1::]",
                 expect: vec![Selector::Root, Selector::ArraySlice(Some(-1), None, None)],
             },
+            Test {
+                input: "$[?(@.a)]",
+                expect: vec![
+                    Selector::Root,
+                    Selector::Filter(FilterExpression::Exists(FilterPath::Relative(vec![
+                        Selector::Root,
+                        Selector::DotMemberName("a".to_owned()),
+                    ]))),
+                ],
+            },
+            Test {
+                input: "$..[?(@.a)]",
+                expect: vec![
+                    Selector::Root,
+                    Selector::DecendantFilter(FilterExpression::Exists(FilterPath::Relative(
+                        vec![Selector::Root, Selector::DotMemberName("a".to_owned())],
+                    ))),
+                ],
+            },
+            Test {
+                input: "$.items[?(@.price < 10)]",
+                expect: vec![
+                    Selector::Root,
+                    Selector::DotMemberName("items".to_owned()),
+                    Selector::Filter(FilterExpression::Comparison(
+                        Comparable::Path(FilterPath::Relative(vec![
+                            Selector::Root,
+                            Selector::DotMemberName("price".to_owned()),
+                        ])),
+                        ComparisonOperator::Lt,
+                        Comparable::Literal(Value::from(10)),
+                    )),
+                ],
+            },
+            Test {
+                input: r#"$[?@.a=="b"||!(@.c>=$.d)&&@[0]!=null]"#,
+                expect: vec![
+                    Selector::Root,
+                    Selector::Filter(FilterExpression::Or(vec![
+                        FilterExpression::Comparison(
+                            Comparable::Path(FilterPath::Relative(vec![
+                                Selector::Root,
+                                Selector::DotMemberName("a".to_owned()),
+                            ])),
+                            ComparisonOperator::Eq,
+                            Comparable::Literal(Value::from("b")),
+                        ),
+                        FilterExpression::And(vec![
+                            FilterExpression::Not(Box::new(FilterExpression::Comparison(
+                                Comparable::Path(FilterPath::Relative(vec![
+                                    Selector::Root,
+                                    Selector::DotMemberName("c".to_owned()),
+                                ])),
+                                ComparisonOperator::Ge,
+                                Comparable::Path(FilterPath::Absolute(vec![
+                                    Selector::Root,
+                                    Selector::DotMemberName("d".to_owned()),
+                                ])),
+                            ))),
+                            FilterExpression::Comparison(
+                                Comparable::Path(FilterPath::Relative(vec![
+                                    Selector::Root,
+                                    Selector::ArrayIndex(0),
+                                ])),
+                                ComparisonOperator::Ne,
+                                Comparable::Literal(Value::Null),
+                            ),
+                        ]),
+                    ])),
+                ],
+            },
+            Test {
+                input: "$[?(!@.a && (@.b == true || @.b == -1.5e2))]",
+                expect: vec![
+                    Selector::Root,
+                    Selector::Filter(FilterExpression::And(vec![
+                        FilterExpression::Not(Box::new(FilterExpression::Exists(
+                            FilterPath::Relative(vec![
+                                Selector::Root,
+                                Selector::DotMemberName("a".to_owned()),
+                            ]),
+                        ))),
+                        FilterExpression::Or(vec![
+                            FilterExpression::Comparison(
+                                Comparable::Path(FilterPath::Relative(vec![
+                                    Selector::Root,
+                                    Selector::DotMemberName("b".to_owned()),
+                                ])),
+                                ComparisonOperator::Eq,
+                                Comparable::Literal(Value::Bool(true)),
+                            ),
+                            FilterExpression::Comparison(
+                                Comparable::Path(FilterPath::Relative(vec![
+                                    Selector::Root,
+                                    Selector::DotMemberName("b".to_owned()),
+                                ])),
+                                ComparisonOperator::Eq,
+                                Comparable::Literal(Value::from(-150.0)),
+                            ),
+                        ]),
+                    ])),
+                ],
+            },
         ]
         .iter()
         .for_each(|test| {
@@ -361,14 +625,33 @@ mod tests {
             assert_eq!(selectors, test.expect);
         })
     }
+    #[test]
+    fn kind_tests() {
+        vec![
+            ("$", PathKind::JSONPath),
+            ("$.a[0]", PathKind::JSONPath),
+            ("$..a", PathKind::JSONPath),
+            ("/a/0", PathKind::JSONPath),
+            ("", PathKind::Legacy),
+            (".", PathKind::Legacy),
+            (".a[0]", PathKind::Legacy),
+            ("a.b", PathKind::Legacy),
+            ("..a", PathKind::Legacy),
+        ]
+        .into_iter()
+        .for_each(|(input, expect)| assert_eq!(kind(input), expect, "{input}"));
+    }
+
     #[test]
     fn parse_failure_tests() {
         vec![
-            ".",
+            "..",
+            ".a.",
+            "a..",
+            "$a",
             "()",
             "$.",
             "$..",
-            "..",
             "$.5",
             "$[abc]",
             "$[9999999999999999999999999999999999999999]",
@@ -383,6 +666,15 @@ mod tests {
             "$[::-]",
             "$[:-:]",
             "$[-::]",
+            "$[?]",
+            "$[?()]",
+            "$[?(@.a)",
+            "$[?(@.a <)]",
+            "$[?(@.a = 1)]",
+            "$[?(@.a == 01)]",
+            "$[?(@.a == 1e999)]",
+            "$[?(a == 1)]",
+            "$[?(@.a && )]",
         ]
         .iter()
         .for_each(|input| {
@@ -0,0 +1,224 @@
+//! JSON Pointers according to https://www.rfc-editor.org/rfc/rfc6901
+
+use crate::parser::{Selector, UnionMember};
+use crate::PathSegment;
+
+use serde_json::Value;
+
+/// Split a pointer into its unescaped reference tokens, the empty pointer references the whole
+/// document and has no tokens.
+pub fn tokens(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    if !pointer.starts_with('/') {
+        return Err(format!("invalid json pointer: {pointer}"));
+    }
+    pointer[1..]
+        .split('/')
+        .map(|token| {
+            let mut res = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c != '~' {
+                    res.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => res.push('~'),
+                    Some('1') => res.push('/'),
+                    _ => {
+                        return Err(format!(
+                            "invalid escape sequence in json pointer: {pointer}"
+                        ))
+                    }
+                }
+            }
+            Ok(res)
+        })
+        .collect()
+}
+
+/// Translate a pointer into selectors, tokens that are valid array indices select the member of
+/// that name in objects and the element at that index in arrays. Matching these selectors yields
+/// the same path as resolving the pointer, or no path at all if the pointer can not be resolved.
+pub fn selectors(pointer: &str) -> Result<Vec<Selector>, String> {
+    let mut res = vec![Selector::Root];
+    for token in tokens(pointer)? {
+        res.push(match array_index(&token) {
+            Ok(idx) => match isize::try_from(idx) {
+                Ok(idx) => Selector::Union(vec![
+                    UnionMember::MemberName(token),
+                    UnionMember::ArrayIndex(idx),
+                ]),
+                Err(_) => Selector::DotMemberName(token),
+            },
+            Err(_) => Selector::DotMemberName(token),
+        });
+    }
+    Ok(res)
+}
+
+/// Resolve a pointer against a document. Tokens are member names or array indices depending on
+/// the value they are applied to, so we can only tell which one they are by walking the document.
+pub fn resolve(pointer: &str, val: &Value) -> Result<Vec<PathSegment>, String> {
+    resolve_tokens(&tokens(pointer)?, val)
+}
+
+pub fn resolve_tokens(tokens: &[String], val: &Value) -> Result<Vec<PathSegment>, String> {
+    let mut res = Vec::with_capacity(tokens.len());
+    let mut cur = val;
+    for token in tokens {
+        let (segment, next) = match cur {
+            Value::Object(object) => match object.get(token) {
+                Some(next) => (PathSegment::MemberName(token.clone()), next),
+                None => return Err(format!("no such member: {token}")),
+            },
+            Value::Array(array) => {
+                let idx = array_index(token)?;
+                match array.get(idx) {
+                    Some(next) => (PathSegment::ArrayIndex(idx), next),
+                    None => return Err(format!("array index out of bounds: {token}")),
+                }
+            }
+            _ => return Err(format!("can not reference into a scalar value: {token}")),
+        };
+        res.push(segment);
+        cur = next;
+    }
+    Ok(res)
+}
+
+/// Array indices are decimal numbers without leading zeros.
+pub fn array_index(token: &str) -> Result<usize, String> {
+    let valid = match token.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    };
+    if !valid {
+        return Err(format!("invalid array index: {token}"));
+    }
+    token.parse::<usize>().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn resolve_success_tests() {
+        // example from RFC 6901 section 5
+        let input = json!({
+           "foo": ["bar", "baz"],
+           "": 0,
+           "a/b": 1,
+           "c%d": 2,
+           "e^f": 3,
+           "g|h": 4,
+           "i\\j": 5,
+           "k\"l": 6,
+           " ": 7,
+           "m~n": 8
+        });
+        struct Test<'a> {
+            pointer: &'a str,
+            expect: Vec<PathSegment>,
+        }
+        [
+            Test {
+                pointer: "",
+                expect: vec![],
+            },
+            Test {
+                pointer: "/foo",
+                expect: vec![PathSegment::MemberName("foo".to_owned())],
+            },
+            Test {
+                pointer: "/foo/0",
+                expect: vec![
+                    PathSegment::MemberName("foo".to_owned()),
+                    PathSegment::ArrayIndex(0),
+                ],
+            },
+            Test {
+                pointer: "/",
+                expect: vec![PathSegment::MemberName("".to_owned())],
+            },
+            Test {
+                pointer: "/a~1b",
+                expect: vec![PathSegment::MemberName("a/b".to_owned())],
+            },
+            Test {
+                pointer: "/c%d",
+                expect: vec![PathSegment::MemberName("c%d".to_owned())],
+            },
+            Test {
+                pointer: "/i\\j",
+                expect: vec![PathSegment::MemberName("i\\j".to_owned())],
+            },
+            Test {
+                pointer: "/ ",
+                expect: vec![PathSegment::MemberName(" ".to_owned())],
+            },
+            Test {
+                pointer: "/m~0n",
+                expect: vec![PathSegment::MemberName("m~n".to_owned())],
+            },
+        ]
+        .iter()
+        .for_each(|test| {
+            let segments = resolve(test.pointer, &input)
+                .unwrap_or_else(|e| panic!("error resolving {}: {}", test.pointer, e));
+            assert_eq!(segments, test.expect);
+        })
+    }
+
+    #[test]
+    fn selectors_match_resolved_pointer() {
+        let input = json!({
+            "a": [1, {"b": 2, "0": 3}],
+            "0": {"1": [4, 5]},
+            "c/d": {"e~f": 6}
+        });
+        [
+            "",
+            "/a",
+            "/a/0",
+            "/a/1/b",
+            "/a/1/0",
+            "/0",
+            "/0/1",
+            "/0/1/1",
+            "/c~1d/e~0f",
+        ]
+        .iter()
+        .for_each(|pointer| {
+            let resolved = resolve(pointer, &input).expect("error resolve");
+            let matched = crate::matches(selectors(pointer).expect("error selectors"), &input)
+                .expect("error matches");
+            assert_eq!(matched, vec![resolved], "{pointer}");
+        });
+        ["/b", "/a/2", "/a/-", "/a/01", "/0/1/-1"]
+            .iter()
+            .for_each(|pointer| {
+                let matched = crate::matches(selectors(pointer).expect("error selectors"), &input)
+                    .expect("error matches");
+                assert!(matched.is_empty(), "{pointer}");
+            });
+    }
+
+    #[test]
+    fn resolve_failure_tests() {
+        let input = json!({"a": [1, {"b": 2}], "c": 3});
+        [
+            "a", "/d", "/a/2", "/a/-", "/a/01", "/a/b", "/a/1/c", "/c/0", "/a~2", "/a~",
+        ]
+        .iter()
+        .for_each(|pointer| {
+            resolve(pointer, &input).expect_err(&format!("expected error resolving {pointer}"));
+        })
+    }
+}
@@ -0,0 +1,52 @@
+use crate::jsonpath::{map_each_mut, write_reply, MapAction};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = args.next_string()?;
+    let mut values = vec![];
+    for arg in args {
+        values.push(from_str::<Value>(arg.try_as_str()?)?);
+    }
+    if values.is_empty() {
+        return Err(RedisError::WrongArity);
+    }
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    let mut lengths = vec![];
+    let changed = map_each_mut(
+        path.as_str(),
+        val,
+        &mut |v: &mut Value| match v.as_array_mut() {
+            Some(array) => {
+                array.extend(values.iter().cloned());
+                lengths.push(Some(RedisValue::Integer(array.len() as i64)));
+                MapAction::Mutated
+            }
+            None => {
+                lengths.push(None);
+                MapAction::Keep
+            }
+        },
+    )?;
+
+    let reply = write_reply(&path, lengths)?;
+    if changed > 0 {
+        notify(ctx, "json.arrappend", &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
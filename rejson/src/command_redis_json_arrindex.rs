@@ -0,0 +1,59 @@
+use crate::jsonpath::{get, read_reply};
+use crate::rejson::REDIS_JSON_TYPE;
+use redis_module::{Context, NextArg, RedisResult, RedisString, RedisValue};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = args.next_string()?;
+    let val = args.next_string()?;
+    let jsn = from_str::<Value>(&val)?;
+    let start = match args.next() {
+        Some(v) => v.parse_integer()?,
+        None => 0,
+    };
+    let stop = match args.next() {
+        Some(v) => v.parse_integer()?,
+        None => 0,
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+    let doc = match key_value {
+        Some(v) => v,
+        None => return Ok(RedisValue::Null),
+    };
+    let matches = match get(&path, doc) {
+        Ok(v) => v,
+        Err(_) => return Ok(RedisValue::Null),
+    };
+    read_reply(
+        &path,
+        matches
+            .iter()
+            .map(|v| {
+                v.as_array()
+                    .map(|array| RedisValue::Integer(index_of(array, &jsn, start, stop)))
+            })
+            .collect(),
+    )
+}
+
+// Negative bounds count from the end of the array, a stop of 0 means the end of the array.
+// Values are compared exactly, i.e. 1 and 1.0 are not considered to be equal.
+fn index_of(array: &[Value], needle: &Value, start: i64, stop: i64) -> i64 {
+    let len = array.len() as i64;
+    let normalize = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+    let start = normalize(start);
+    let stop = if stop == 0 { len } else { normalize(stop) };
+    if start >= stop {
+        return -1;
+    }
+    array[start as usize..stop as usize]
+        .iter()
+        .position(|v| v == needle)
+        .map_or(-1, |i| start + i as i64)
+}
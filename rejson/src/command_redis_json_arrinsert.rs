@@ -0,0 +1,76 @@
+use crate::jsonpath::{get, in_match_order, map_each_deepest_first_mut, write_reply, MapAction};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = args.next_string()?;
+    let index = args.next_i64()?;
+    let mut values = vec![];
+    for arg in args {
+        values.push(from_str::<Value>(arg.try_as_str()?)?);
+    }
+    if values.is_empty() {
+        return Err(RedisError::WrongArity);
+    }
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    // check every match before inserting so that an invalid index leaves the document untouched
+    let insert_at = |len: usize| {
+        let len = len as i64;
+        let at = if index < 0 { len + index } else { index };
+        if at < 0 || at > len {
+            None
+        } else {
+            Some(at as usize)
+        }
+    };
+    for v in get(&path, val)? {
+        if let Some(array) = v.as_array() {
+            if insert_at(array.len()).is_none() {
+                return Err(RedisError::Str("index out of bounds"));
+            }
+        }
+    }
+
+    let mut lengths = vec![];
+    let changed =
+        map_each_deepest_first_mut(path.as_str(), val, &mut |pos, v: &mut Value| match v
+            .as_array_mut()
+        {
+            Some(array) => match insert_at(array.len()) {
+                Some(at) => {
+                    array.splice(at..at, values.iter().cloned());
+                    lengths.push((pos, Some(RedisValue::Integer(array.len() as i64))));
+                    MapAction::Mutated
+                }
+                None => {
+                    lengths.push((pos, None));
+                    MapAction::Keep
+                }
+            },
+            None => {
+                lengths.push((pos, None));
+                MapAction::Keep
+            }
+        })?;
+
+    let reply = write_reply(&path, in_match_order(lengths))?;
+    if changed > 0 {
+        notify(ctx, "json.arrinsert", &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
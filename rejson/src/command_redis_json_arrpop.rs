@@ -0,0 +1,64 @@
+use crate::jsonpath::{in_match_order, map_each_deepest_first_mut, write_reply, MapAction};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::{to_vec, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = match args.next_string() {
+        Ok(v) => v,
+        Err(_) => ".".to_owned(),
+    };
+    let index = match args.next() {
+        Some(v) => v.parse_integer()?,
+        None => -1,
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    let mut popped = vec![];
+    let changed =
+        map_each_deepest_first_mut(path.as_str(), val, &mut |pos, v: &mut Value| match v
+            .as_array_mut()
+        {
+            Some(array) if array.is_empty() => {
+                popped.push((pos, Some(RedisValue::Null)));
+                MapAction::Keep
+            }
+            Some(array) => {
+                let elem = array.remove(wrapped_index(index, array.len()));
+                // serializing a Value can not fail
+                popped.push((pos, Some(RedisValue::StringBuffer(to_vec(&elem).unwrap()))));
+                MapAction::Mutated
+            }
+            None => {
+                popped.push((pos, None));
+                MapAction::Keep
+            }
+        })?;
+
+    let reply = write_reply(&path, in_match_order(popped))?;
+    if changed > 0 {
+        notify(ctx, "json.arrpop", &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
+
+// Out of range indices are clamped to the first and the last element respectively.
+fn wrapped_index(index: i64, len: usize) -> usize {
+    let len = len as i64;
+    let index = if index < 0 { len + index } else { index };
+    index.clamp(0, len - 1) as usize
+}
@@ -0,0 +1,78 @@
+use crate::jsonpath::{in_match_order, map_each_deepest_first_mut, write_reply, MapAction};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::Value;
+use std::ops::Range;
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = args.next_string()?;
+    let start = args.next_i64()?;
+    let stop = args.next_i64()?;
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    let mut lengths = vec![];
+    let changed =
+        map_each_deepest_first_mut(path.as_str(), val, &mut |pos, v: &mut Value| match v
+            .as_array_mut()
+        {
+            Some(array) => {
+                let range = trimmed_range(start, stop, array.len());
+                let unchanged = range.len() == array.len();
+                array.truncate(range.end);
+                array.drain(..range.start);
+                lengths.push((pos, Some(RedisValue::Integer(array.len() as i64))));
+                if unchanged {
+                    MapAction::Keep
+                } else {
+                    MapAction::Mutated
+                }
+            }
+            None => {
+                lengths.push((pos, None));
+                MapAction::Keep
+            }
+        })?;
+
+    let reply = write_reply(&path, in_match_order(lengths))?;
+    if changed > 0 {
+        notify(ctx, "json.arrtrim", &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
+
+// Both bounds are inclusive and negative bounds count from the end of the array. Bounds past
+// the end are clamped to the last element, the array is emptied when start is past the end or
+// comes after stop.
+fn trimmed_range(start: i64, stop: i64, len: usize) -> Range<usize> {
+    let len = len as i64;
+    if len == 0 || start >= len {
+        return 0..0;
+    }
+    let normalize = |i: i64| {
+        if i < 0 {
+            (len + i).max(0)
+        } else {
+            i.min(len - 1)
+        }
+    };
+    let start = normalize(start);
+    let stop = normalize(stop);
+    if start > stop {
+        return 0..0;
+    }
+    start as usize..stop as usize + 1
+}
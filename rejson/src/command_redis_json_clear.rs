@@ -1,4 +1,4 @@
-use crate::jsonpath::{map_each, MapAction};
+use crate::jsonpath::{map_each_mut, MapAction};
 use crate::rejson::*;
 use redis_module::{Context, NextArg, RedisResult, RedisString, RedisValue};
 use serde_json::{Number, Value};
@@ -24,15 +24,19 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     };
 
     let mut i = 0;
-    let res = map_each(path.as_str(), val, &mut |v: &Value| {
-        let mut vv = v.clone();
-        if clear(&mut vv) {
+    map_each_mut(path.as_str(), val, &mut |v: &mut Value| {
+        if clear(v) {
             i += 1;
+            MapAction::Mutated
+        } else {
+            MapAction::Keep
         }
-        MapAction::ReplaceWith(vv)
     })?;
 
-    key_ptr.set_value(&REDIS_JSON_TYPE, res)?;
+    if i > 0 {
+        notify(ctx, "json.clear", &key);
+        ctx.replicate_verbatim();
+    }
     Ok(RedisValue::Integer(i))
 }
 
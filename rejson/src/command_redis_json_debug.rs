@@ -0,0 +1,46 @@
+use crate::jsonpath::{get, kind, read_reply, PathKind};
+use crate::rejson::{mem_usage, REDIS_JSON_TYPE};
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::Value;
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let subcommand = args.next_string()?;
+    match subcommand.to_uppercase().as_str() {
+        "MEMORY" => memory(ctx, args),
+        _ => Err(RedisError::String(format!(
+            "unknown subcommand '{subcommand}'"
+        ))),
+    }
+}
+
+fn memory(ctx: &Context, mut args: impl Iterator<Item = RedisString>) -> RedisResult {
+    let key = args.next_arg()?;
+    let path = match args.next_string() {
+        Ok(v) => v,
+        Err(_) => ".".to_owned(),
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+    // like upstream a key that doesn't exist uses no memory
+    let jsn = match key_value {
+        Some(v) => v,
+        None => {
+            return Ok(match kind(&path) {
+                PathKind::JSONPath => RedisValue::Array(vec![]),
+                PathKind::Legacy => RedisValue::Integer(0),
+            })
+        }
+    };
+    let matches = get(&path, jsn)?;
+    read_reply(
+        &path,
+        matches
+            .iter()
+            .map(|v| Some(RedisValue::Integer(mem_usage(v) as i64)))
+            .collect(),
+    )
+}
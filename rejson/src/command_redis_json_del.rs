@@ -1,4 +1,4 @@
-use crate::jsonpath::{map_each, MapAction};
+use crate::jsonpath::{map_each_mut, parse, MapAction, Selector};
 use crate::rejson::*;
 use redis_module::{Context, NextArg, RedisResult, RedisString, RedisValue};
 use serde_json::Value;
@@ -9,7 +9,7 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     let key = args.next_arg()?;
     let path = match args.next_string() {
         Ok(v) => v,
-        Err(_) => "$".to_owned(),
+        Err(_) => ".".to_owned(),
     };
     args.done()?;
 
@@ -23,16 +23,21 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
         }
     };
 
-    if path == "$" {
+    if parse(&path)? == vec![Selector::Root] {
         key_ptr.delete()?;
+        notify(ctx, "json.del", &key);
+        ctx.replicate_verbatim();
         return Ok(RedisValue::Integer(1));
     }
 
     let mut i = 0;
-    let res = map_each(path.as_str(), val, &mut |_| {
+    map_each_mut(path.as_str(), val, &mut |_| {
         i += 1;
         MapAction::Delete
     })?;
-    key_ptr.set_value(&REDIS_JSON_TYPE, res)?;
+    if i > 0 {
+        notify(ctx, "json.del", &key);
+        ctx.replicate_verbatim();
+    }
     Ok(RedisValue::Integer(i))
 }
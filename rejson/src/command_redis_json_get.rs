@@ -1,9 +1,9 @@
-use crate::jsonpath::get;
+use crate::jsonpath::{get, kind, path_does_not_exist, PathKind};
 use crate::rejson::REDIS_JSON_TYPE;
 use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
 use serde::ser::Serialize;
 use serde_json::ser::{Formatter, Serializer};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use std::collections::HashMap;
 use std::io;
 
@@ -32,7 +32,7 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
             }
         }
     }
-    let paths = args.collect::<Vec<RedisString>>();
+    let paths = args.map(|p| p.to_string()).collect::<Vec<String>>();
 
     let key_ptr = ctx.open_key_writable(&key);
     let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
@@ -42,32 +42,44 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     };
 
     let res = match paths.len() {
-        0 => Ok(json!(jsn)),
-        1 => match get(&paths[0].to_string(), jsn) {
-            Ok(v) => Ok(json!(v)),
+        0 => json!(jsn),
+        1 => match get(&paths[0], jsn) {
+            Ok(v) => match kind(&paths[0]) {
+                PathKind::JSONPath => json!(v),
+                PathKind::Legacy => json!(first_match(&paths[0], v)?),
+            },
             Err(_) => return Ok(RedisValue::Null),
         },
+        // legacy paths only reply with their first match when none of the paths is JSONPath
+        _ if paths.iter().all(|p| kind(p) == PathKind::Legacy) => {
+            let mut m = Map::new();
+            for p in paths.iter() {
+                m.insert(p.clone(), first_match(p, get(p, jsn)?)?.clone());
+            }
+            Value::Object(m)
+        }
         _ => {
             let m = paths
                 .iter()
-                .map(|p| p.to_string())
-                .map(|p| (p.clone(), get(&p, jsn)))
+                .map(|p| (p.clone(), get(p, jsn)))
                 .filter(|(_, r)| r.is_ok())
                 .map(|(p, r)| (p, r.unwrap()))
                 .collect::<HashMap<String, Vec<&Value>>>();
-            Ok(json!(m))
+            json!(m)
         }
     };
 
-    match res {
-        Ok(v) => {
-            let mut w = Vec::with_capacity(128);
-            let mut ser = Serializer::with_formatter(&mut w, fmt);
-            v.serialize(&mut ser)?;
-            Ok(RedisValue::StringBuffer(w))
-        }
-        Err(e) => Err(RedisError::String(e)),
-    }
+    let mut w = Vec::with_capacity(128);
+    let mut ser = Serializer::with_formatter(&mut w, fmt);
+    res.serialize(&mut ser)?;
+    Ok(RedisValue::StringBuffer(w))
+}
+
+fn first_match<'a>(path: &str, matches: Vec<&'a Value>) -> Result<&'a Value, RedisError> {
+    matches
+        .into_iter()
+        .next()
+        .ok_or_else(|| path_does_not_exist(path))
 }
 
 pub struct CustomFormatter {
@@ -0,0 +1,35 @@
+use crate::jsonpath::{merge_mut, parse, Selector};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, REDIS_OK};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = args.next_string()?;
+    let val = args.next_string()?;
+    let patch = from_str::<Value>(&val)?;
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let changed = match key_value {
+        Some(v) => merge_mut(path.as_str(), v, &patch)?,
+        None => {
+            if parse(&path)? != vec![Selector::Root] {
+                return Err(RedisError::Str(ERR_NEW_OBJECTS_AT_ROOT));
+            }
+            let mut res = Value::Null;
+            merge_mut(path.as_str(), &mut res, &patch)?;
+            key_ptr.set_value(&REDIS_JSON_TYPE, res)?;
+            1
+        }
+    };
+    if changed > 0 {
+        notify(ctx, "json.merge", &key);
+        ctx.replicate_verbatim();
+    }
+    REDIS_OK
+}
@@ -0,0 +1,36 @@
+use crate::jsonpath::{get, kind, PathKind};
+use crate::rejson::REDIS_JSON_TYPE;
+use redis_module::{Context, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::{to_vec, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1).collect::<Vec<RedisString>>();
+
+    if args.len() < 2 {
+        return Err(RedisError::WrongArity);
+    }
+    let path = args.pop().unwrap().try_as_str()?.to_owned();
+
+    let mut res = Vec::with_capacity(args.len());
+    for key in args {
+        let key_ptr = ctx.open_key_writable(&key);
+        // missing keys and keys holding other types are reported as nil
+        let jsn = match key_ptr.get_value::<Value>(&REDIS_JSON_TYPE) {
+            Ok(Some(v)) => v,
+            _ => {
+                res.push(RedisValue::Null);
+                continue;
+            }
+        };
+        let matches = get(&path, jsn)?;
+        // legacy paths reply with the first match of each key or nil if there is none
+        res.push(match kind(&path) {
+            PathKind::JSONPath => RedisValue::StringBuffer(to_vec(&matches)?),
+            PathKind::Legacy => match matches.first() {
+                Some(v) => RedisValue::StringBuffer(to_vec(v)?),
+                None => RedisValue::Null,
+            },
+        });
+    }
+    Ok(RedisValue::Array(res))
+}
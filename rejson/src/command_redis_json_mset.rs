@@ -0,0 +1,95 @@
+use crate::jsonpath::{check_set, parse, set_mut, Selector};
+use crate::rejson::*;
+use redis_module::{Context, RedisError, RedisResult, RedisString, REDIS_OK};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let args = args.into_iter().skip(1).collect::<Vec<RedisString>>();
+
+    if args.is_empty() || args.len() % 3 != 0 {
+        return Err(RedisError::WrongArity);
+    }
+
+    let mut triples = Vec::with_capacity(args.len() / 3);
+    for triple in args.chunks(3) {
+        let path = triple[1].try_as_str()?;
+        let selectors = parse(path)?;
+        let jsn = from_str::<Value>(triple[2].try_as_str()?)?;
+        triples.push((&triple[0], path, selectors, jsn));
+    }
+
+    // Check every triple before writing any of them, that way a failure in one of them leaves
+    // all keys untouched. Setting a value only fails when the path can't be matched, so for keys
+    // that occur once matching the path up front is enough. A key can occur more then once in
+    // which case its triples are applied in order to a copy of the document that is written at
+    // the end, since every triple changes what the next one matches.
+    let mut staged: Vec<(&RedisString, Option<Value>, usize)> = vec![];
+    for (key, path, selectors, jsn) in &triples {
+        let key_ptr = ctx.open_key_writable(key);
+        let cur = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+        let occurrences = triples
+            .iter()
+            .filter(|(k, ..)| k.as_slice() == key.as_slice())
+            .count();
+        if occurrences == 1 {
+            match cur {
+                Some(v) => check_set(path, v)?,
+                None if *selectors == vec![Selector::Root] => {}
+                None => return Err(RedisError::Str(ERR_NEW_OBJECTS_AT_ROOT)),
+            }
+            continue;
+        }
+        let i = match staged
+            .iter()
+            .position(|(k, ..)| k.as_slice() == key.as_slice())
+        {
+            Some(i) => i,
+            None => {
+                staged.push((key, cur.cloned(), 0));
+                staged.len() - 1
+            }
+        };
+        let (_, doc, changed) = &mut staged[i];
+        match doc {
+            Some(v) => *changed += set_mut(path, v, jsn)?,
+            None if *selectors == vec![Selector::Root] => {
+                *doc = Some(jsn.clone());
+                *changed += 1;
+            }
+            None => return Err(RedisError::Str(ERR_NEW_OBJECTS_AT_ROOT)),
+        }
+    }
+
+    let mut written = false;
+    for (key, path, _, jsn) in triples {
+        if staged.iter().any(|(k, ..)| k.as_slice() == key.as_slice()) {
+            continue;
+        }
+        let key_ptr = ctx.open_key_writable(key);
+        let changed = match key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)? {
+            Some(v) => set_mut(path, v, &jsn)?,
+            None => {
+                key_ptr.set_value(&REDIS_JSON_TYPE, jsn)?;
+                1
+            }
+        };
+        if changed > 0 {
+            notify(ctx, "json.mset", key);
+            written = true;
+        }
+    }
+    for (key, doc, changed) in staged {
+        if changed == 0 {
+            continue;
+        }
+        if let Some(v) = doc {
+            ctx.open_key_writable(key).set_value(&REDIS_JSON_TYPE, v)?;
+        }
+        notify(ctx, "json.mset", key);
+        written = true;
+    }
+    if written {
+        ctx.replicate_verbatim();
+    }
+    REDIS_OK
+}
@@ -0,0 +1,125 @@
+use crate::jsonpath::{get, kind, map_each_mut, write_reply, MapAction, PathKind};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::{from_str, to_vec, Number, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    num_op(ctx, args, NumOp::IncrBy)
+}
+
+pub enum NumOp {
+    IncrBy,
+    MultBy,
+}
+
+impl NumOp {
+    // Integers stay integers as long as both operands are integers and the result fits into
+    // an i64, otherwise the result is a float. Integers above i64::MAX are valid operands.
+    fn apply(&self, cur: &Number, by: &Number) -> Result<Number, RedisError> {
+        if let (Some(a), Some(b)) = (integer(cur), integer(by)) {
+            let res = match self {
+                NumOp::IncrBy => a.checked_add(b),
+                NumOp::MultBy => a.checked_mul(b),
+            };
+            return match res.and_then(|v| i64::try_from(v).ok()) {
+                Some(v) => Ok(Number::from(v)),
+                None => Err(RedisError::Str("result is an overflow of i64")),
+            };
+        }
+        let (a, b) = (cur.as_f64().unwrap(), by.as_f64().unwrap());
+        let res = match self {
+            NumOp::IncrBy => a + b,
+            NumOp::MultBy => a * b,
+        };
+        match Number::from_f64(res) {
+            Some(v) => Ok(v),
+            None => Err(RedisError::Str("result is not a finite number")),
+        }
+    }
+
+    fn event(&self) -> &'static str {
+        match self {
+            NumOp::IncrBy => "json.numincrby",
+            NumOp::MultBy => "json.nummultby",
+        }
+    }
+}
+
+fn integer(n: &Number) -> Option<i128> {
+    match n.as_i64() {
+        Some(v) => Some(v.into()),
+        None => n.as_u64().map(i128::from),
+    }
+}
+
+pub fn num_op(ctx: &Context, args: Vec<RedisString>, op: NumOp) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = args.next_string()?;
+    let by = match from_str::<Value>(&args.next_string()?)? {
+        Value::Number(n) => n,
+        _ => return Err(RedisError::Str("expected a number")),
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    // check every match before updating so that an overflow leaves the document untouched
+    for v in get(&path, val)? {
+        if let Value::Number(n) = v {
+            op.apply(n, &by)?;
+        }
+    }
+
+    let mut results = vec![];
+    let mut err = None;
+    let changed = map_each_mut(path.as_str(), val, &mut |v: &mut Value| match v {
+        Value::Number(n) => match op.apply(n, &by) {
+            Ok(res) => {
+                *n = res;
+                results.push(Value::Number(n.clone()));
+                MapAction::Mutated
+            }
+            Err(e) => {
+                err = Some(e);
+                MapAction::Keep
+            }
+        },
+        _ => {
+            results.push(Value::Null);
+            MapAction::Keep
+        }
+    })?;
+    if let Some(e) = err {
+        return Err(e);
+    }
+
+    // JSONPath replies with a json array of the results, legacy paths with the last result
+    let reply = match kind(&path) {
+        PathKind::JSONPath => RedisValue::StringBuffer(to_vec(&Value::Array(results))?),
+        PathKind::Legacy => write_reply(
+            &path,
+            results
+                .iter()
+                .map(|v| match v {
+                    Value::Null => None,
+                    v => to_vec(v).ok().map(RedisValue::StringBuffer),
+                })
+                .collect(),
+        )?,
+    };
+    if changed > 0 {
+        notify(ctx, op.event(), &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
@@ -0,0 +1,6 @@
+use crate::command_redis_json_numincrby::{num_op, NumOp};
+use redis_module::{Context, RedisResult, RedisString};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    num_op(ctx, args, NumOp::MultBy)
+}
@@ -0,0 +1,42 @@
+use crate::jsonpath::{get, read_reply};
+use crate::rejson::REDIS_JSON_TYPE;
+use redis_module::{Context, NextArg, RedisResult, RedisString, RedisValue};
+use serde_json::Value;
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = match args.next_string() {
+        Ok(v) => v,
+        Err(_) => ".".to_owned(),
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+    let jsn = match key_value {
+        Some(v) => v,
+        None => return Ok(RedisValue::Null),
+    };
+    let matches = match get(&path, jsn) {
+        Ok(v) => v,
+        Err(_) => return Ok(RedisValue::Null),
+    };
+    read_reply(
+        &path,
+        matches
+            .iter()
+            .map(|v| {
+                v.as_object().map(|object| {
+                    RedisValue::Array(
+                        object
+                            .keys()
+                            .map(|k| RedisValue::StringBuffer(k.as_bytes().to_vec()))
+                            .collect(),
+                    )
+                })
+            })
+            .collect(),
+    )
+}
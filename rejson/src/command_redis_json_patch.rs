@@ -0,0 +1,29 @@
+use crate::jsonpath::patch_mut;
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, REDIS_OK};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let val = args.next_string()?;
+    let operations = from_str::<Value>(&val)?;
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    if patch_mut(val, &operations)? > 0 {
+        notify(ctx, "json.patch", &key);
+        ctx.replicate_verbatim();
+    }
+    REDIS_OK
+}
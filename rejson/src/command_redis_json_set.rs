@@ -1,7 +1,10 @@
-use crate::jsonpath::set;
+use crate::jsonpath::{set_create_mut, set_mut};
 use crate::rejson::*;
+use redis_module::raw::RedisModule_Replicate;
 use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue, REDIS_OK};
-use serde_json::{from_str, Value};
+use serde_json::{from_str, Map, Value};
+
+use core::ffi::c_char;
 
 pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     let mut args = args.into_iter().skip(1);
@@ -11,37 +14,92 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     let val = args.next_string()?;
     let jsn = from_str::<Value>(&val)?;
 
-    let nx_or_xx = match args.next_string() {
-        Ok(v) => match v.to_uppercase().as_str() {
-            "NX" => Some(Mod::NX),
-            "XX" => Some(Mod::XX),
+    let mut nx_or_xx = None;
+    let mut create = false;
+    while let Ok(v) = args.next_string() {
+        match v.to_uppercase().as_str() {
+            "NX" if nx_or_xx.is_none() => nx_or_xx = Some(Mod::NX),
+            "XX" if nx_or_xx.is_none() => nx_or_xx = Some(Mod::XX),
+            // create missing objects along the path instead of silently doing nothing
+            "CREATE" if !create => create = true,
             _ => return Err(RedisError::WrongArity),
-        },
-        Err(_) => None,
-    };
-    args.done()?;
+        }
+    }
 
     let key_ptr = ctx.open_key_writable(&key);
     let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
 
-    match key_value {
+    let changed = match key_value {
         Some(v) => {
             if is_nx(nx_or_xx) {
                 return Ok(RedisValue::Null);
             }
-            let res = set(path.as_str(), v, &jsn)?;
-            key_ptr.set_value(&REDIS_JSON_TYPE, res)?;
+            if create {
+                set_create_mut(path.as_str(), v, &jsn)?;
+                1
+            } else {
+                set_mut(path.as_str(), v, &jsn)?
+            }
         }
         None => {
             if is_xx(nx_or_xx) {
                 return Ok(RedisValue::Null);
             }
-            key_ptr.set_value(&REDIS_JSON_TYPE, jsn)?;
+            if create {
+                let mut res = Value::Object(Map::new());
+                set_create_mut(path.as_str(), &mut res, &jsn)?;
+                key_ptr.set_value(&REDIS_JSON_TYPE, res)?;
+            } else {
+                key_ptr.set_value(&REDIS_JSON_TYPE, jsn)?;
+            }
+            1
         }
     };
+    if changed == 0 {
+        return REDIS_OK;
+    }
+    notify(ctx, "json.set", &key);
+    // whether NX or XX apply depends on the state of the key, replicate the resulting write only
+    if nx_or_xx.is_some() {
+        replicate_set(ctx, &key, &path, &val, create);
+    } else {
+        ctx.replicate_verbatim();
+    }
     REDIS_OK
 }
 
+// CREATE is kept since whether objects are created along the path depends on the write only.
+fn replicate_set(ctx: &Context, key: &RedisString, path: &str, val: &str, create: bool) {
+    let cmd = b"JSON.SET\0".as_ptr().cast::<c_char>();
+    unsafe {
+        let replicate = RedisModule_Replicate.unwrap();
+        if create {
+            replicate(
+                ctx.ctx,
+                cmd,
+                b"sbbc\0".as_ptr().cast::<c_char>(),
+                key.inner,
+                path.as_ptr(),
+                path.len(),
+                val.as_ptr(),
+                val.len(),
+                b"CREATE\0".as_ptr().cast::<c_char>(),
+            );
+        } else {
+            replicate(
+                ctx.ctx,
+                cmd,
+                b"sbb\0".as_ptr().cast::<c_char>(),
+                key.inner,
+                path.as_ptr(),
+                path.len(),
+                val.as_ptr(),
+                val.len(),
+            );
+        }
+    }
+}
+
 #[derive(PartialEq, Eq)]
 enum Mod {
     NX,
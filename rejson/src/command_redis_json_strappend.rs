@@ -0,0 +1,49 @@
+use crate::jsonpath::{map_each_mut, write_reply, MapAction};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::{from_str, Value};
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let (path, val) = match (args.next_string()?, args.next_string()) {
+        (path, Ok(val)) => (path, val),
+        (val, Err(_)) => (".".to_owned(), val),
+    };
+    let suffix = match from_str::<Value>(&val)? {
+        Value::String(s) => s,
+        _ => return Err(RedisError::Str("expected a json string")),
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    let mut lengths = vec![];
+    let changed = map_each_mut(path.as_str(), val, &mut |v: &mut Value| match v {
+        Value::String(s) => {
+            s.push_str(&suffix);
+            lengths.push(Some(RedisValue::Integer(s.len() as i64)));
+            MapAction::Mutated
+        }
+        _ => {
+            lengths.push(None);
+            MapAction::Keep
+        }
+    })?;
+
+    let reply = write_reply(&path, lengths)?;
+    if changed > 0 {
+        notify(ctx, "json.strappend", &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
@@ -0,0 +1,51 @@
+use crate::jsonpath::{kind, map_each_mut, write_reply, MapAction, PathKind};
+use crate::rejson::*;
+use redis_module::{Context, NextArg, RedisError, RedisResult, RedisString, RedisValue};
+use serde_json::Value;
+
+pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
+    let mut args = args.into_iter().skip(1);
+
+    let key = args.next_arg()?;
+    let path = match args.next_string() {
+        Ok(v) => v,
+        Err(_) => "$".to_owned(),
+    };
+    args.done()?;
+
+    let key_ptr = ctx.open_key_writable(&key);
+    let key_value = key_ptr.get_value::<Value>(&REDIS_JSON_TYPE)?;
+
+    let val = match key_value {
+        Some(v) => v,
+        None => {
+            return Err(RedisError::Str(ERR_KEY_DOES_NOT_EXIST));
+        }
+    };
+
+    // legacy paths reply with the new value as a string instead of an integer
+    let legacy = kind(&path) == PathKind::Legacy;
+    let mut toggled = vec![];
+    let changed = map_each_mut(path.as_str(), val, &mut |v: &mut Value| match v {
+        Value::Bool(b) => {
+            *b = !*b;
+            toggled.push(Some(if legacy {
+                RedisValue::StringBuffer(b.to_string().into_bytes())
+            } else {
+                RedisValue::Integer(*b as i64)
+            }));
+            MapAction::Mutated
+        }
+        _ => {
+            toggled.push(None);
+            MapAction::Keep
+        }
+    })?;
+
+    let reply = write_reply(&path, toggled)?;
+    if changed > 0 {
+        notify(ctx, "json.toggle", &key);
+        ctx.replicate_verbatim();
+    }
+    Ok(reply)
+}
@@ -1,4 +1,4 @@
-use crate::jsonpath::get;
+use crate::jsonpath::{get, kind, PathKind};
 use crate::rejson::REDIS_JSON_TYPE;
 use redis_module::{Context, NextArg, RedisResult, RedisString, RedisValue};
 use serde_json::Value;
@@ -9,7 +9,7 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
     let key = args.next_arg()?;
     let path = match args.next_string() {
         Ok(v) => v,
-        Err(_) => "$".to_string(),
+        Err(_) => ".".to_string(),
     };
     args.done()?;
 
@@ -19,20 +19,19 @@ pub fn cmd(ctx: &Context, args: Vec<RedisString>) -> RedisResult {
         Some(v) => v,
         None => return Ok(RedisValue::Null),
     };
-    if path == "$" {
-        return Ok(RedisValue::StringBuffer(json_type(jsn).as_bytes().to_vec()));
-    }
     let matches = match get(&path, jsn) {
         Ok(v) => v,
         Err(_) => return Ok(RedisValue::Null),
     };
-    return Ok(RedisValue::Array(
-        matches
-            .iter()
-            .map(|v| json_type(v).as_bytes().to_vec())
-            .map(RedisValue::StringBuffer)
-            .collect(),
-    ));
+    let mut types = matches
+        .iter()
+        .map(|v| json_type(v).as_bytes().to_vec())
+        .map(RedisValue::StringBuffer);
+    // unlike other commands legacy paths report a missing path as nil
+    Ok(match kind(&path) {
+        PathKind::JSONPath => RedisValue::Array(types.collect()),
+        PathKind::Legacy => types.next().unwrap_or(RedisValue::Null),
+    })
 }
 
 fn json_type(m: &Value) -> String {
@@ -1,8 +1,25 @@
-use redis_module::RedisError;
+use crate::rejson::ERR_WRONG_TYPE;
+use redis_module::{RedisError, RedisResult, RedisValue};
 use serde_json::Value;
 
+pub use jsonpath::parser::{PathKind, Selector};
 pub use jsonpath::MapAction;
 
+pub fn kind(path: &str) -> PathKind {
+    jsonpath::parser::kind(path)
+}
+
+pub fn path_does_not_exist(path: &str) -> RedisError {
+    RedisError::String(format!("Path '{path}' does not exist"))
+}
+
+pub fn parse(path: &str) -> Result<Vec<Selector>, RedisError> {
+    match jsonpath::parser::parse(path) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(RedisError::String(e)),
+    }
+}
+
 pub fn get<'a>(path: &str, val: &'a Value) -> Result<Vec<&'a Value>, RedisError> {
     match jsonpath::get(path, val) {
         Ok(v) => Ok(v),
@@ -10,20 +27,93 @@ pub fn get<'a>(path: &str, val: &'a Value) -> Result<Vec<&'a Value>, RedisError>
     }
 }
 
-pub fn set(path: &str, val: &Value, to: &Value) -> Result<Value, RedisError> {
-    match jsonpath::set(path, val, to) {
+pub fn set_mut(path: &str, val: &mut Value, to: &Value) -> Result<usize, RedisError> {
+    match jsonpath::set_mut(path, val, to) {
         Ok(v) => Ok(v),
         Err(e) => Err(RedisError::String(e)),
     }
 }
 
-pub fn map_each(
+pub fn check_set(path: &str, val: &Value) -> Result<(), RedisError> {
+    match jsonpath::check_set(path, val) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(RedisError::String(e)),
+    }
+}
+
+pub fn set_create_mut(path: &str, val: &mut Value, to: &Value) -> Result<(), RedisError> {
+    match jsonpath::set_create_mut(path, val, to) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(RedisError::String(e)),
+    }
+}
+
+pub fn merge_mut(path: &str, val: &mut Value, patch: &Value) -> Result<usize, RedisError> {
+    match jsonpath::merge_mut(path, val, patch) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(RedisError::String(e)),
+    }
+}
+
+pub fn patch_mut(val: &mut Value, operations: &Value) -> Result<usize, RedisError> {
+    match jsonpath::patch_mut(val, operations) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(RedisError::String(e)),
+    }
+}
+
+pub fn map_each_mut(
+    path: &str,
+    val: &mut Value,
+    fun: &mut dyn FnMut(&mut Value) -> MapAction<Value>,
+) -> Result<usize, RedisError> {
+    match jsonpath::map_each_mut(path, val, fun) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(RedisError::String(e)),
+    }
+}
+
+pub fn map_each_deepest_first_mut(
     path: &str,
-    val: &Value,
-    fun: &mut dyn FnMut(&Value) -> MapAction<Value>,
-) -> Result<Value, RedisError> {
-    match jsonpath::map_each(path, val, fun) {
+    val: &mut Value,
+    fun: &mut dyn FnMut(usize, &mut Value) -> MapAction<Value>,
+) -> Result<usize, RedisError> {
+    match jsonpath::map_each_deepest_first_mut(path, val, fun) {
         Ok(v) => Ok(v),
         Err(e) => Err(RedisError::String(e)),
     }
 }
+
+// Puts the replies collected by map_each_deepest_first_mut back into match order.
+pub fn in_match_order(mut replies: Vec<(usize, Option<RedisValue>)>) -> Vec<Option<RedisValue>> {
+    replies.sort_by_key(|(pos, _)| *pos);
+    replies.into_iter().map(|(_, reply)| reply).collect()
+}
+
+// JSONPath replies with an entry per match where None marks a match of the wrong type, which is
+// reported as nil. Legacy paths reply with a single value and raise errors when nothing matched
+// or no match had the right type, reads reply with the first and writes with the last match.
+pub fn read_reply(path: &str, replies: Vec<Option<RedisValue>>) -> RedisResult {
+    reply(path, replies, false)
+}
+
+pub fn write_reply(path: &str, replies: Vec<Option<RedisValue>>) -> RedisResult {
+    reply(path, replies, true)
+}
+
+fn reply(path: &str, replies: Vec<Option<RedisValue>>, last: bool) -> RedisResult {
+    if kind(path) == PathKind::JSONPath {
+        return Ok(RedisValue::Array(
+            replies
+                .into_iter()
+                .map(|r| r.unwrap_or(RedisValue::Null))
+                .collect(),
+        ));
+    }
+    if replies.is_empty() {
+        return Err(path_does_not_exist(path));
+    }
+    let mut found = replies.into_iter().flatten();
+    let res = if last { found.last() } else { found.next() };
+    res.ok_or(RedisError::Str(ERR_WRONG_TYPE))
+}
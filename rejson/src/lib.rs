@@ -1,26 +1,64 @@
 #[macro_use]
 extern crate redis_module;
 
+mod command_redis_json_arrappend;
+mod command_redis_json_arrindex;
+mod command_redis_json_arrinsert;
+mod command_redis_json_arrlen;
+mod command_redis_json_arrpop;
+mod command_redis_json_arrtrim;
 mod command_redis_json_clear;
+mod command_redis_json_debug;
 mod command_redis_json_del;
 mod command_redis_json_get;
+mod command_redis_json_merge;
+mod command_redis_json_mget;
+mod command_redis_json_mset;
+mod command_redis_json_numincrby;
+mod command_redis_json_nummultby;
+mod command_redis_json_objkeys;
+mod command_redis_json_objlen;
+mod command_redis_json_patch;
 mod command_redis_json_set;
+mod command_redis_json_strappend;
+mod command_redis_json_strlen;
+mod command_redis_json_toggle;
 mod command_redis_json_type;
 mod jsonpath;
+mod rdb;
 mod rejson;
 
-use crate::rejson::REDIS_JSON_TYPE;
+use crate::rejson::{init, LEGACY_REDIS_JSON_TYPE, REDIS_JSON_TYPE};
 
 redis_module! {
     name: "json",
     version: 1,
-    data_types: [REDIS_JSON_TYPE],
+    data_types: [REDIS_JSON_TYPE, LEGACY_REDIS_JSON_TYPE],
+    init: init,
     commands: [
-        ["json.clear", command_redis_json_clear::cmd, "write", 0, 0, 0],
-        ["json.del", command_redis_json_del::cmd, "write", 0, 0, 0],
-        ["json.forget", command_redis_json_del::cmd, "write", 0, 0, 0],
-        ["json.get", command_redis_json_get::cmd, "readonly", 0, 0, 0],
-        ["json.set", command_redis_json_set::cmd, "write deny-oom", 0, 0, 0],
-        ["json.type", command_redis_json_type::cmd, "readonly", 0, 0, 0],
+        ["json.arrappend", command_redis_json_arrappend::cmd, "write deny-oom", 1, 1, 1],
+        ["json.arrindex", command_redis_json_arrindex::cmd, "readonly", 1, 1, 1],
+        ["json.arrinsert", command_redis_json_arrinsert::cmd, "write deny-oom", 1, 1, 1],
+        ["json.arrlen", command_redis_json_arrlen::cmd, "readonly", 1, 1, 1],
+        ["json.arrpop", command_redis_json_arrpop::cmd, "write", 1, 1, 1],
+        ["json.arrtrim", command_redis_json_arrtrim::cmd, "write", 1, 1, 1],
+        ["json.clear", command_redis_json_clear::cmd, "write", 1, 1, 1],
+        ["json.debug", command_redis_json_debug::cmd, "readonly", 2, 2, 1],
+        ["json.del", command_redis_json_del::cmd, "write", 1, 1, 1],
+        ["json.forget", command_redis_json_del::cmd, "write", 1, 1, 1],
+        ["json.get", command_redis_json_get::cmd, "readonly", 1, 1, 1],
+        ["json.merge", command_redis_json_merge::cmd, "write deny-oom", 1, 1, 1],
+        ["json.mget", command_redis_json_mget::cmd, "readonly", 1, -2, 1],
+        ["json.mset", command_redis_json_mset::cmd, "write deny-oom", 1, -1, 3],
+        ["json.numincrby", command_redis_json_numincrby::cmd, "write", 1, 1, 1],
+        ["json.nummultby", command_redis_json_nummultby::cmd, "write", 1, 1, 1],
+        ["json.objkeys", command_redis_json_objkeys::cmd, "readonly", 1, 1, 1],
+        ["json.objlen", command_redis_json_objlen::cmd, "readonly", 1, 1, 1],
+        ["json.patch", command_redis_json_patch::cmd, "write deny-oom", 1, 1, 1],
+        ["json.set", command_redis_json_set::cmd, "write deny-oom", 1, 1, 1],
+        ["json.strappend", command_redis_json_strappend::cmd, "write deny-oom", 1, 1, 1],
+        ["json.strlen", command_redis_json_strlen::cmd, "readonly", 1, 1, 1],
+        ["json.toggle", command_redis_json_toggle::cmd, "write", 1, 1, 1],
+        ["json.type", command_redis_json_type::cmd, "readonly", 1, 1, 1],
     ],
 }
@@ -0,0 +1,232 @@
+use redis_module::raw::{load_double, load_signed, load_string_buffer, load_unsigned};
+use redis_module::redisraw::bindings::RedisModuleIO;
+use serde_json::{Map, Number, Value};
+
+// Binary encoding of documents stored in RDB files since encoding version 4. Every node starts
+// with a tag byte, lengths are unsigned LEB128 varints and numbers are stored as 8 byte little
+// endian integers or floats. Objects are stored as a length followed by key and value pairs in
+// member order, strings and keys as a length followed by their utf-8 bytes.
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UNSIGNED: u8 = 3;
+const TAG_SIGNED: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_STRING: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_OBJECT: u8 = 8;
+
+// Limits the recursion of the decoders, which would otherwise overflow the stack on corrupted
+// payloads claiming deeply nested documents.
+const MAX_DEPTH: usize = 1024;
+
+pub fn encode(v: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    encode_value(v, &mut out);
+    out
+}
+
+pub fn decode(buf: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader { buf, pos: 0 };
+    let v = reader.value(0)?;
+    match reader.pos == buf.len() {
+        true => Ok(v),
+        false => Err(format!("{} trailing bytes", buf.len() - reader.pos)),
+    }
+}
+
+fn encode_value(v: &Value, out: &mut Vec<u8>) {
+    match v {
+        Value::Null => out.push(TAG_NULL),
+        Value::Bool(false) => out.push(TAG_FALSE),
+        Value::Bool(true) => out.push(TAG_TRUE),
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64() {
+                out.push(TAG_UNSIGNED);
+                out.extend_from_slice(&n.to_le_bytes());
+            } else if let Some(n) = n.as_i64() {
+                out.push(TAG_SIGNED);
+                out.extend_from_slice(&n.to_le_bytes());
+            } else {
+                out.push(TAG_FLOAT);
+                // without arbitrary precision every number is representable by one of the three
+                out.extend_from_slice(&n.as_f64().unwrap().to_le_bytes());
+            }
+        }
+        Value::String(s) => {
+            out.push(TAG_STRING);
+            encode_str(s, out);
+        }
+        Value::Array(a) => {
+            out.push(TAG_ARRAY);
+            encode_len(a.len(), out);
+            for v in a {
+                encode_value(v, out);
+            }
+        }
+        Value::Object(o) => {
+            out.push(TAG_OBJECT);
+            encode_len(o.len(), out);
+            for (k, v) in o {
+                encode_str(k, out);
+                encode_value(v, out);
+            }
+        }
+    }
+}
+
+fn encode_str(s: &str, out: &mut Vec<u8>) {
+    encode_len(s.len(), out);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn encode_len(mut len: usize, out: &mut Vec<u8>) {
+    while len >= 0x80 {
+        out.push((len as u8) | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "nesting deeper than {MAX_DEPTH} at offset {}",
+                self.pos
+            ));
+        }
+        let tag = self.bytes(1)?[0];
+        Ok(match tag {
+            TAG_NULL => Value::Null,
+            TAG_FALSE => Value::Bool(false),
+            TAG_TRUE => Value::Bool(true),
+            TAG_UNSIGNED => Value::from(u64::from_le_bytes(self.array()?)),
+            TAG_SIGNED => Value::from(i64::from_le_bytes(self.array()?)),
+            TAG_FLOAT => {
+                let n = f64::from_le_bytes(self.array()?);
+                Value::Number(Number::from_f64(n).ok_or(format!("invalid number {n}"))?)
+            }
+            TAG_STRING => Value::String(self.string()?),
+            TAG_ARRAY => {
+                let len = self.len()?;
+                // every element takes at least one byte, which bounds the allocation by the input
+                let mut a = Vec::with_capacity(len.min(self.buf.len() - self.pos));
+                for _ in 0..len {
+                    a.push(self.value(depth + 1)?);
+                }
+                Value::Array(a)
+            }
+            TAG_OBJECT => {
+                let len = self.len()?;
+                let mut o = Map::new();
+                for _ in 0..len {
+                    let k = self.string()?;
+                    o.insert(k, self.value(depth + 1)?);
+                }
+                Value::Object(o)
+            }
+            tag => return Err(format!("unknown tag {tag} at offset {}", self.pos - 1)),
+        })
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.len()?;
+        let pos = self.pos;
+        match std::str::from_utf8(self.bytes(len)?) {
+            Ok(s) => Ok(s.to_owned()),
+            Err(_) => Err(format!("invalid utf-8 in string at offset {pos}")),
+        }
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let mut len = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let b = self.bytes(1)?[0];
+            len |= ((b & 0x7f) as usize) << shift;
+            if b & 0x80 == 0 {
+                return Ok(len);
+            }
+        }
+        Err(format!("invalid length at offset {}", self.pos))
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        // bytes returns exactly N bytes
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        match self.buf.get(self.pos..).and_then(|b| b.get(..n)) {
+            Some(b) => {
+                self.pos += n;
+                Ok(b)
+            }
+            None => Err(format!("unexpected end of data at offset {}", self.pos)),
+        }
+    }
+}
+
+// Node types of the encoding version 0 used by the first, C based, releases of upstream RedisJSON.
+// Every node is stored as its type followed by its data, the members of objects are stored as
+// key and value nodes.
+const NODE_NULL: u64 = 0x1;
+const NODE_STRING: u64 = 0x2;
+const NODE_NUMBER: u64 = 0x4;
+const NODE_INTEGER: u64 = 0x8;
+const NODE_BOOLEAN: u64 = 0x10;
+const NODE_DICT: u64 = 0x20;
+const NODE_ARRAY: u64 = 0x40;
+const NODE_KEYVAL: u64 = 0x80;
+
+pub fn load_tree(rdb: *mut RedisModuleIO, depth: usize) -> Result<Value, String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("nesting deeper than {MAX_DEPTH}"));
+    }
+    Ok(match load_unsigned(rdb).map_err(|e| e.to_string())? {
+        NODE_NULL => Value::Null,
+        NODE_STRING => Value::String(load_str(rdb)?),
+        NODE_NUMBER => {
+            let n = load_double(rdb).map_err(|e| e.to_string())?;
+            Value::Number(Number::from_f64(n).ok_or(format!("invalid number {n}"))?)
+        }
+        NODE_INTEGER => Value::from(load_signed(rdb).map_err(|e| e.to_string())?),
+        NODE_BOOLEAN => Value::Bool(load_str(rdb)? == "1"),
+        NODE_DICT => {
+            let len = load_unsigned(rdb).map_err(|e| e.to_string())?;
+            let mut o = Map::new();
+            for _ in 0..len {
+                match load_unsigned(rdb).map_err(|e| e.to_string())? {
+                    NODE_KEYVAL => {
+                        let k = load_str(rdb)?;
+                        o.insert(k, load_tree(rdb, depth + 1)?);
+                    }
+                    node => return Err(format!("unexpected node type {node} in object")),
+                }
+            }
+            Value::Object(o)
+        }
+        NODE_ARRAY => {
+            let len = load_unsigned(rdb).map_err(|e| e.to_string())?;
+            let mut a = Vec::new();
+            for _ in 0..len {
+                a.push(load_tree(rdb, depth + 1)?);
+            }
+            Value::Array(a)
+        }
+        node => return Err(format!("unexpected node type {node}")),
+    })
+}
+
+fn load_str(rdb: *mut RedisModuleIO) -> Result<String, String> {
+    let buf = load_string_buffer(rdb).map_err(|e| e.to_string())?;
+    match std::str::from_utf8(buf.as_ref()) {
+        Ok(s) => Ok(s.to_owned()),
+        Err(_) => Err("invalid utf-8 in string".to_owned()),
+    }
+}
@@ -1,14 +1,46 @@
 use redis_module::native_types::RedisType;
-use redis_module::raw::{load_string, save_string, RedisModuleTypeMethods};
-use redis_module::redisraw::bindings::RedisModuleIO;
+use redis_module::raw::{
+    load_string, load_string_buffer, load_unsigned, save_slice, RedisModuleTypeMethods,
+    RedisModule_CloseKey, RedisModule_CreateString, RedisModule_DigestAddStringBuffer,
+    RedisModule_DigestEndSequence, RedisModule_EmitAOF, RedisModule_FreeString,
+    RedisModule_GetContextFlags, RedisModule_GetContextFromIO, RedisModule_GetDbIdFromIO,
+    RedisModule_GetExpire, RedisModule_GetKeyNameFromIO, RedisModule_LogIOError,
+    RedisModule_OpenKey, RedisModule_SelectDb, RedisModule_SetExpire, RedisModule_StringPtrLen,
+    RedisModule_SubscribeToServerEvent,
+};
+use redis_module::redisraw::bindings::{
+    RedisModuleCtx, RedisModuleDigest, RedisModuleEvent, RedisModuleIO, RedisModuleKey,
+    RedisModuleString, REDISMODULE_CTX_FLAGS_LOADING, REDISMODULE_EVENT_LOADING,
+    REDISMODULE_NO_EXPIRE, REDISMODULE_READ, REDISMODULE_SUBEVENT_LOADING_ENDED, REDISMODULE_WRITE,
+};
+use redis_module::{Context, NotifyEvent, RedisString, Status};
 
+use crate::rdb;
 use serde_json::{from_str, to_string, Value};
 
-use core::ffi::c_void;
+use core::ffi::{c_char, c_int, c_void};
+use std::ffi::CString;
+use std::mem::size_of;
 use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
-pub const MODULE_TYPE_NAME: &str = "RedisJSON";
-pub const REDIS_JSON_TYPE_VERSION: i32 = 0;
+// The type name is shared with upstream RedisJSON so that we can load its RDB files. Versions 0 to 3
+// are used by upstream, our own version 4 can't be loaded by upstream, see rdb_load.
+pub const MODULE_TYPE_NAME: &str = "ReJSON-RL";
+pub const REDIS_JSON_TYPE_VERSION: i32 = 4;
+
+// The type name used by earlier versions of this module, whose RDB files we still load.
+pub const LEGACY_MODULE_TYPE_NAME: &str = "RedisJSON";
+
+// Documents serializing to more bytes are split into several commands by the AOF rewrite.
+pub const AOF_CHUNK_SIZE: usize = 1024 * 1024;
+
+pub const ERR_KEY_DOES_NOT_EXIST: &str =
+    "could not perform this operation on a key that doesn't exist";
+pub const ERR_NEW_OBJECTS_AT_ROOT: &str = "new objects must be created at the root";
+pub const ERR_WRONG_TYPE: &str = "wrong type of path value";
 
 pub static REDIS_JSON_TYPE: RedisType = RedisType::new(
     MODULE_TYPE_NAME,
@@ -17,40 +49,499 @@ pub static REDIS_JSON_TYPE: RedisType = RedisType::new(
         version: redis_module::TYPE_METHOD_VERSION,
         rdb_save: Some(redis_json_rdb_save),
         rdb_load: Some(redis_json_rdb_load),
-        aof_rewrite: None,
+        aof_rewrite: Some(redis_json_aof_rewrite),
         free: Some(redis_json_rdb_free),
-        mem_usage: None,
-        digest: None,
+        mem_usage: Some(redis_json_mem_usage),
+        digest: Some(redis_json_digest),
         aux_load: None,
         aux_save: None,
         aux_save_triggers: 0,
         free_effort: None,
         unlink: None,
-        copy: None,
+        copy: Some(redis_json_copy),
         defrag: None,
     },
 );
 
-unsafe extern "C" fn redis_json_rdb_load(rdb: *mut RedisModuleIO, _: i32) -> *mut c_void {
-    match load_string(rdb) {
-        Err(_) => ptr::null_mut(),
-        Ok(v) => match from_str::<Value>(&v.to_string_lossy()) {
-            Err(_) => ptr::null_mut(),
-            Ok(v) => Box::into_raw(Box::new(v)).cast::<c_void>(),
-        },
+// Only registered to load RDB files written by earlier versions of this module, keys of this type
+// are converted to REDIS_JSON_TYPE once loading ended. Saving them uses the current encoding.
+pub static LEGACY_REDIS_JSON_TYPE: RedisType = RedisType::new(
+    LEGACY_MODULE_TYPE_NAME,
+    REDIS_JSON_TYPE_VERSION,
+    RedisModuleTypeMethods {
+        version: redis_module::TYPE_METHOD_VERSION,
+        rdb_save: Some(redis_json_rdb_save),
+        rdb_load: Some(legacy_redis_json_rdb_load),
+        aof_rewrite: Some(redis_json_aof_rewrite),
+        free: Some(redis_json_rdb_free),
+        mem_usage: Some(redis_json_mem_usage),
+        digest: Some(redis_json_digest),
+        aux_load: None,
+        aux_save: None,
+        aux_save_triggers: 0,
+        free_effort: None,
+        unlink: None,
+        copy: Some(redis_json_copy),
+        defrag: None,
+    },
+);
+
+// Informs keyspace notification subscribers about a write, all our events are of the module class.
+pub fn notify(ctx: &Context, event: &str, key: &RedisString) {
+    ctx.notify_keyspace_event(NotifyEvent::MODULE, event, key);
+}
+
+// Whether keys that can't be loaded are skipped instead of failing the load, set by the
+// RDB_LOAD_ERROR module argument.
+static SKIP_RDB_LOAD_ERRORS: AtomicBool = AtomicBool::new(false);
+
+// Redis fails the load whenever rdb_load returns null, skipped keys are therefore loaded as null
+// documents and deleted once loading ended.
+static SKIPPED_KEYS: Mutex<Vec<(i32, Vec<u8>)>> = Mutex::new(Vec::new());
+
+// Keys loaded with the legacy type, which are converted once loading ended.
+static LEGACY_KEYS: Mutex<Vec<(i32, Vec<u8>)>> = Mutex::new(Vec::new());
+
+pub fn init(ctx: &Context, args: &[RedisString]) -> Status {
+    let args = args.iter().map(|a| a.to_string_lossy()).collect::<Vec<_>>();
+    for arg in args.chunks(2) {
+        match (
+            arg[0].to_uppercase().as_str(),
+            arg.get(1).map(|v| v.to_uppercase()),
+        ) {
+            ("RDB_LOAD_ERROR", Some(v)) if v == "FAIL" || v == "SKIP" => {
+                SKIP_RDB_LOAD_ERRORS.store(v == "SKIP", Ordering::Relaxed);
+            }
+            _ => {
+                ctx.log_warning(&format!("invalid module arguments {}", args.join(" ")));
+                return Status::Err;
+            }
+        }
+    }
+    unsafe {
+        RedisModule_SubscribeToServerEvent.unwrap()(
+            ctx.ctx,
+            RedisModuleEvent {
+                id: REDISMODULE_EVENT_LOADING as u64,
+                dataver: 1,
+            },
+            Some(redis_json_loading_event),
+        );
     }
+    Status::Ok
 }
 
-unsafe extern "C" fn redis_json_rdb_save(rdb: *mut RedisModuleIO, v: *mut c_void) {
-    match to_string(&v.cast::<Value>().as_ref()) {
-        Err(e) => panic!("{}", e),
-        Ok(v) => save_string(rdb, &v),
+unsafe extern "C" fn redis_json_loading_event(
+    ctx: *mut RedisModuleCtx,
+    _: RedisModuleEvent,
+    subevent: u64,
+    _: *mut c_void,
+) {
+    let skipped = SKIPPED_KEYS.lock().unwrap().drain(..).collect::<Vec<_>>();
+    let legacy = LEGACY_KEYS.lock().unwrap().drain(..).collect::<Vec<_>>();
+    if subevent != REDISMODULE_SUBEVENT_LOADING_ENDED as u64 {
+        return;
+    }
+    let context = Context::new(ctx);
+    for (db, key) in skipped {
+        RedisModule_SelectDb.unwrap()(ctx, db);
+        // the placeholder is always there to be deleted
+        let _ = context.open_key_writable(&key_string(ctx, &key)).delete();
+    }
+    for (db, key) in legacy {
+        RedisModule_SelectDb.unwrap()(ctx, db);
+        let name = key_string(ctx, &key);
+        let expire = with_raw_key(ctx, &name, REDISMODULE_READ, |key| {
+            RedisModule_GetExpire.unwrap()(key)
+        });
+        let key = context.open_key_writable(&name);
+        // A key of the other type can't be set before deleting it, the value is moved out first
+        // so that deleting only frees the null left behind. Deleting drops the expire as well,
+        // it is set again afterwards.
+        if let Ok(Some(v)) = key.get_value::<Value>(&LEGACY_REDIS_JSON_TYPE) {
+            let v = std::mem::take(v);
+            let converted = key
+                .delete()
+                .and_then(|_| key.set_value(&REDIS_JSON_TYPE, v))
+                .is_ok();
+            if converted && expire != REDISMODULE_NO_EXPIRE as i64 {
+                with_raw_key(ctx, &name, REDISMODULE_WRITE, |key| {
+                    RedisModule_SetExpire.unwrap()(key, expire)
+                });
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn redis_json_rdb_load(rdb: *mut RedisModuleIO, encver: i32) -> *mut c_void {
+    load_key(rdb, rdb_load(rdb, encver))
+}
+
+unsafe extern "C" fn legacy_redis_json_rdb_load(
+    rdb: *mut RedisModuleIO,
+    encver: i32,
+) -> *mut c_void {
+    // RESTORE has nothing to convert the key afterwards, only the dataset is loaded with this type
+    let res = match loading(rdb) {
+        true => legacy_rdb_load(rdb, encver),
+        false => Err(format!(
+            "{LEGACY_MODULE_TYPE_NAME} values are only loaded with the dataset"
+        )),
+    };
+    if res.is_ok() {
+        let db = RedisModule_GetDbIdFromIO.unwrap()(rdb);
+        LEGACY_KEYS.lock().unwrap().push((db, key_name(rdb)));
+    }
+    load_key(rdb, res)
+}
+
+unsafe fn load_key(rdb: *mut RedisModuleIO, res: Result<Value, String>) -> *mut c_void {
+    let v = match res {
+        Ok(v) => v,
+        Err(e) => {
+            let key = key_name(rdb);
+            let name = String::from_utf8_lossy(&key);
+            // Skipping is limited to loading the dataset, RESTORE reports the error instead.
+            // Errors of the surrounding RDB format are handled by Redis and always fail the load.
+            if !loading(rdb) || !SKIP_RDB_LOAD_ERRORS.load(Ordering::Relaxed) {
+                log_io_error(rdb, &format!("failed to load key '{name}': {e}"));
+                return ptr::null_mut();
+            }
+            log_io_error(rdb, &format!("skipping key '{name}': {e}"));
+            let db = RedisModule_GetDbIdFromIO.unwrap()(rdb);
+            SKIPPED_KEYS.lock().unwrap().push((db, key));
+            Value::Null
+        }
+    };
+    Box::into_raw(Box::new(v)).cast::<c_void>()
+}
+
+unsafe fn loading(rdb: *mut RedisModuleIO) -> bool {
+    let ctx = RedisModule_GetContextFromIO.unwrap()(rdb);
+    RedisModule_GetContextFlags.unwrap()(ctx) as u32 & REDISMODULE_CTX_FLAGS_LOADING != 0
+}
+
+unsafe fn key_name(rdb: *mut RedisModuleIO) -> Vec<u8> {
+    let key = RedisModule_GetKeyNameFromIO.unwrap()(rdb);
+    if key.is_null() {
+        return vec![];
+    }
+    let mut len = 0;
+    let ptr = RedisModule_StringPtrLen.unwrap()(key, &mut len);
+    slice::from_raw_parts(ptr.cast::<u8>(), len).to_vec()
+}
+
+// For what RedisKeyWritable doesn't offer, like reading the expire of a key.
+unsafe fn with_raw_key<T>(
+    ctx: *mut RedisModuleCtx,
+    name: &RedisString,
+    mode: u32,
+    fun: impl FnOnce(*mut RedisModuleKey) -> T,
+) -> T {
+    let key = RedisModule_OpenKey.unwrap()(ctx, name.inner, mode as c_int);
+    let res = fun(key.cast::<RedisModuleKey>());
+    RedisModule_CloseKey.unwrap()(key);
+    res
+}
+
+// Key names are arbitrary bytes while RedisString::create only takes strings without nul bytes.
+unsafe fn key_string(ctx: *mut RedisModuleCtx, key: &[u8]) -> RedisString {
+    let inner = RedisModule_CreateString.unwrap()(ctx, key.as_ptr().cast::<c_char>(), key.len());
+    let res = RedisString::new(ctx, inner);
+    // new retains the string it wraps
+    RedisModule_FreeString.unwrap()(ctx, inner);
+    res
+}
+
+unsafe fn log_io_error(rdb: *mut RedisModuleIO, message: &str) {
+    // interior nul bytes can only come from key names
+    let message = CString::new(message.replace('\0', "\\0")).unwrap();
+    RedisModule_LogIOError.unwrap()(
+        rdb,
+        b"warning\0".as_ptr().cast::<c_char>(),
+        b"%s\0".as_ptr().cast::<c_char>(),
+        message.as_ptr(),
+    );
+}
+
+// Versions 0 to 3 are written by upstream RedisJSON. Version 0 is the tree encoding of its C based
+// releases, versions 2 and 3 store a JSON string where version 2 is followed by data of a search
+// module. Version 4 is our binary encoding.
+fn rdb_load(rdb: *mut RedisModuleIO, encver: i32) -> Result<Value, String> {
+    match encver {
+        0 => rdb::load_tree(rdb, 0),
+        _ => load_encoding(rdb, encver),
+    }
+}
+
+// Earlier versions of this module stored version 0 documents as a JSON string, the other versions
+// are the same as for the current type name.
+fn legacy_rdb_load(rdb: *mut RedisModuleIO, encver: i32) -> Result<Value, String> {
+    match encver {
+        0 => load_json(load_string(rdb)),
+        _ => load_encoding(rdb, encver),
+    }
+}
+
+fn load_encoding(rdb: *mut RedisModuleIO, encver: i32) -> Result<Value, String> {
+    let json = match encver {
+        3 => load_string(rdb),
+        2 => load_string(rdb).and_then(|json| {
+            if load_unsigned(rdb)? > 0 {
+                load_string(rdb)?;
+                load_string(rdb)?;
+            }
+            Ok(json)
+        }),
+        REDIS_JSON_TYPE_VERSION => {
+            let buf = load_string_buffer(rdb).map_err(|e| e.to_string())?;
+            return rdb::decode(buf.as_ref());
+        }
+        _ => return Err(format!("unsupported encoding version {encver}")),
     };
+    load_json(json)
+}
+
+fn load_json<E: ToString>(json: Result<RedisString, E>) -> Result<Value, String> {
+    let json = json.map_err(|e| e.to_string())?;
+    from_str(&json.to_string_lossy()).map_err(|e| e.to_string())
+}
+
+unsafe extern "C" fn redis_json_rdb_save(rdb: *mut RedisModuleIO, v: *mut c_void) {
+    save_slice(rdb, &rdb::encode(&*v.cast::<Value>()));
 }
 
 unsafe extern "C" fn redis_json_rdb_free(v: *mut c_void) {
     if v.is_null() {
         return;
     }
-    drop(Box::from_raw(v.cast::<RedisType>()));
+    drop(Box::from_raw(v.cast::<Value>()));
+}
+
+unsafe extern "C" fn redis_json_copy(
+    _: *mut RedisModuleString,
+    _: *mut RedisModuleString,
+    v: *const c_void,
+) -> *mut c_void {
+    Box::into_raw(Box::new((*v.cast::<Value>()).clone())).cast::<c_void>()
+}
+
+unsafe extern "C" fn redis_json_aof_rewrite(
+    aof: *mut RedisModuleIO,
+    key: *mut RedisModuleString,
+    v: *mut c_void,
+) {
+    let emit = RedisModule_EmitAOF.unwrap();
+    let create = RedisModule_CreateString.unwrap();
+    let free = RedisModule_FreeString.unwrap();
+    let v = &*v.cast::<Value>();
+    aof_rewrite(
+        "$",
+        v,
+        &sizes(v),
+        &mut |cmd: &[u8], path: &str, values: &[&str]| {
+            let mut args = values
+                .iter()
+                .map(|v| create(ptr::null_mut(), v.as_ptr().cast::<c_char>(), v.len()))
+                .collect::<Vec<_>>();
+            emit(
+                aof,
+                cmd.as_ptr().cast::<c_char>(),
+                b"sbv\0".as_ptr().cast::<c_char>(),
+                key,
+                path.as_ptr(),
+                path.len(),
+                args.as_mut_ptr(),
+                args.len(),
+            );
+            for arg in args {
+                free(ptr::null_mut(), arg);
+            }
+        },
+    );
+}
+
+// The serialized length of a value and its members, computed bottom up once so that large
+// documents are serialized only once more while they are split. Members containing nulls that
+// aren't inside of arrays can't be sent with JSON.MERGE since it deletes them instead.
+struct Sizes {
+    len: usize,
+    null: bool,
+    members: Vec<Sizes>,
+}
+
+fn sizes(v: &Value) -> Sizes {
+    let members = match v {
+        Value::Object(object) => object.values().map(sizes).collect(),
+        Value::Array(array) => array.iter().map(sizes).collect(),
+        _ => vec![],
+    };
+    // brackets and commas, objects add the keys and a colon for each member
+    let separators = 2 + members.len().saturating_sub(1);
+    let values = members.iter().map(|s| s.len).sum::<usize>();
+    // serializing a Value can not fail
+    let len = match v {
+        Value::Object(object) => {
+            separators
+                + values
+                + object
+                    .keys()
+                    .map(|k| to_string(k).unwrap().len() + 1)
+                    .sum::<usize>()
+        }
+        Value::Array(_) => separators + values,
+        _ => to_string(v).unwrap().len(),
+    };
+    let null = match v {
+        Value::Null => true,
+        Value::Object(_) => members.iter().any(|s| s.null),
+        _ => false,
+    };
+    Sizes { len, null, members }
+}
+
+// Small documents are emitted as a single JSON.SET, larger ones are created empty and filled
+// in batches of at most AOF_CHUNK_SIZE, arrays with JSON.ARRAPPEND and objects with JSON.MERGE.
+// Members that don't fit into a batch are emitted on their own, recursing into those that are
+// too large themselves. Only scalars larger than AOF_CHUNK_SIZE end up in commands exceeding it.
+fn aof_rewrite(path: &str, v: &Value, sizes: &Sizes, emit: &mut dyn FnMut(&[u8], &str, &[&str])) {
+    // serializing a Value can not fail
+    if sizes.len <= AOF_CHUNK_SIZE {
+        emit(b"JSON.SET\0", path, &[&to_string(v).unwrap()]);
+        return;
+    }
+    match v {
+        Value::Object(object) => {
+            emit(b"JSON.SET\0", path, &["{}"]);
+            let mut batch = String::new();
+            for ((k, v), s) in object.iter().zip(&sizes.members) {
+                let k = to_string(k).unwrap();
+                // braces, colon and comma
+                let len = k.len() + s.len + 3;
+                if s.null || len > AOF_CHUNK_SIZE {
+                    merge_batch(path, &mut batch, emit);
+                    aof_rewrite(&format!("{path}[{k}]"), v, s, emit);
+                    continue;
+                }
+                if batch.len() + len > AOF_CHUNK_SIZE {
+                    merge_batch(path, &mut batch, emit);
+                }
+                batch.push(if batch.is_empty() { '{' } else { ',' });
+                batch.push_str(&k);
+                batch.push(':');
+                batch.push_str(&to_string(v).unwrap());
+            }
+            merge_batch(path, &mut batch, emit);
+        }
+        Value::Array(array) => {
+            emit(b"JSON.SET\0", path, &["[]"]);
+            let mut batch = vec![];
+            let mut len = 0;
+            for (i, (v, s)) in array.iter().zip(&sizes.members).enumerate() {
+                if s.len > AOF_CHUNK_SIZE {
+                    append_batch(path, &mut batch, emit);
+                    len = 0;
+                    emit(b"JSON.ARRAPPEND\0", path, &["null"]);
+                    aof_rewrite(&format!("{path}[{i}]"), v, s, emit);
+                    continue;
+                }
+                if len + s.len > AOF_CHUNK_SIZE {
+                    append_batch(path, &mut batch, emit);
+                    len = 0;
+                }
+                batch.push(to_string(v).unwrap());
+                len += s.len;
+            }
+            append_batch(path, &mut batch, emit);
+        }
+        _ => emit(b"JSON.SET\0", path, &[&to_string(v).unwrap()]),
+    }
+}
+
+// The batch holds the members of an object without its closing brace.
+fn merge_batch(path: &str, batch: &mut String, emit: &mut dyn FnMut(&[u8], &str, &[&str])) {
+    if !batch.is_empty() {
+        batch.push('}');
+        emit(b"JSON.MERGE\0", path, &[batch]);
+        batch.clear();
+    }
+}
+
+fn append_batch(path: &str, batch: &mut Vec<String>, emit: &mut dyn FnMut(&[u8], &str, &[&str])) {
+    if !batch.is_empty() {
+        let values = batch.iter().map(String::as_str).collect::<Vec<_>>();
+        emit(b"JSON.ARRAPPEND\0", path, &values);
+        batch.clear();
+    }
+}
+
+unsafe extern "C" fn redis_json_mem_usage(v: *const c_void) -> usize {
+    mem_usage(&*v.cast::<Value>())
+}
+
+// Estimates the bytes allocated for a value including the value itself. Unused capacity of
+// strings and arrays is counted, object members are charged for their key and the hash and index
+// kept by the underlying map.
+pub fn mem_usage(v: &Value) -> usize {
+    size_of::<Value>()
+        + match v {
+            Value::String(s) => s.capacity(),
+            Value::Array(a) => {
+                (a.capacity() - a.len()) * size_of::<Value>()
+                    + a.iter().map(mem_usage).sum::<usize>()
+            }
+            Value::Object(o) => o
+                .iter()
+                .map(|(k, v)| {
+                    size_of::<String>() + k.capacity() + 2 * size_of::<usize>() + mem_usage(v)
+                })
+                .sum(),
+            _ => 0,
+        }
+}
+
+unsafe extern "C" fn redis_json_digest(md: *mut RedisModuleDigest, v: *mut c_void) {
+    let mut canonical = String::new();
+    canonical_json(&*v.cast::<Value>(), &mut canonical);
+    RedisModule_DigestAddStringBuffer.unwrap()(
+        md,
+        canonical.as_ptr().cast::<c_char>(),
+        canonical.len(),
+    );
+    RedisModule_DigestEndSequence.unwrap()(md);
+}
+
+// The digest is computed over a canonical serialization which sorts object members by key, so
+// documents only differing in the order of their members share a digest. Everything else is
+// significant, including the order of array elements and the representation of numbers, `1` and
+// `1.0` have different digests just like they are returned differently by JSON.GET.
+fn canonical_json(v: &Value, out: &mut String) {
+    match v {
+        Value::Array(a) => {
+            out.push('[');
+            for (i, v) in a.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                canonical_json(v, out);
+            }
+            out.push(']');
+        }
+        Value::Object(o) => {
+            let mut members = o.iter().collect::<Vec<_>>();
+            members.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+            out.push('{');
+            for (i, (k, v)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                // serializing a Value can not fail
+                out.push_str(&to_string(k).unwrap());
+                out.push(':');
+                canonical_json(v, out);
+            }
+            out.push('}');
+        }
+        _ => out.push_str(&to_string(v).unwrap()),
+    }
 }
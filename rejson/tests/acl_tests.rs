@@ -0,0 +1,93 @@
+use common::Ctx;
+use test_context::test_context;
+
+mod common;
+
+// A connection of a user that may only access keys starting with allowed.
+fn restricted_connection(ctx: &mut Ctx) -> redis::Connection {
+    let mut con = ctx.connection();
+
+    redis::cmd("ACL")
+        .arg("SETUSER")
+        .arg("restricted")
+        .arg("on")
+        .arg(">password")
+        .arg("~allowed*")
+        .arg("+@all")
+        .execute(&mut con);
+    redis::cmd("JSON.SET")
+        .arg("allowed")
+        .arg("$")
+        .arg(r#"{"a":[1],"b":"c","d":1,"e":true}"#)
+        .execute(&mut con);
+    redis::cmd("JSON.SET")
+        .arg("denied")
+        .arg("$")
+        .arg(r#"{"a":[1],"b":"c","d":1,"e":true}"#)
+        .execute(&mut con);
+
+    let mut con = ctx.connection();
+    redis::cmd("AUTH")
+        .arg("restricted")
+        .arg("password")
+        .execute(&mut con);
+    con
+}
+
+fn commands<'a>(key: &'a str, other: &'a str) -> Vec<Vec<&'a str>> {
+    vec![
+        vec!["JSON.ARRAPPEND", key, "$.a", "2"],
+        vec!["JSON.ARRINDEX", key, "$.a", "1"],
+        vec!["JSON.ARRINSERT", key, "$.a", "0", "0"],
+        vec!["JSON.ARRLEN", key, "$.a"],
+        vec!["JSON.ARRPOP", key, "$.a"],
+        vec!["JSON.ARRTRIM", key, "$.a", "0", "0"],
+        vec!["JSON.CLEAR", key, "$.a"],
+        vec!["JSON.DEBUG", "MEMORY", key],
+        vec!["JSON.DEL", key, "$.f"],
+        vec!["JSON.FORGET", key, "$.f"],
+        vec!["JSON.GET", key],
+        vec!["JSON.MERGE", key, "$", r#"{"f":1}"#],
+        vec!["JSON.MGET", other, key, "$"],
+        vec!["JSON.MSET", other, "$", "1", key, "$.f", "1"],
+        vec!["JSON.NUMINCRBY", key, "$.d", "1"],
+        vec!["JSON.NUMMULTBY", key, "$.d", "1"],
+        vec!["JSON.OBJKEYS", key],
+        vec!["JSON.OBJLEN", key],
+        vec!["JSON.PATCH", key, "[]"],
+        vec!["JSON.SET", key, "$.f", "1"],
+        vec!["JSON.STRAPPEND", key, "$.b", r#""d""#],
+        vec!["JSON.STRLEN", key, "$.b"],
+        vec!["JSON.TOGGLE", key, "$.e"],
+        vec!["JSON.TYPE", key],
+    ]
+}
+
+#[test_context(Ctx)]
+#[test]
+fn key_outside_pattern_is_denied(ctx: &mut Ctx) {
+    let mut con = restricted_connection(ctx);
+
+    for command in commands("denied", "allowed") {
+        match redis::cmd(command[0])
+            .arg(&command[1..])
+            .query::<redis::Value>(&mut con)
+        {
+            Ok(_) => panic!("{} should have been denied", command[0]),
+            Err(e) => assert_eq!(e.code(), Some("NOPERM"), "{}: {e}", command[0]),
+        }
+    }
+}
+
+#[test_context(Ctx)]
+#[test]
+fn key_inside_pattern_is_allowed(ctx: &mut Ctx) {
+    let mut con = restricted_connection(ctx);
+
+    for command in commands("allowed", "allowed-other") {
+        redis::cmd(command[0])
+            .arg(&command[1..])
+            .query::<redis::Value>(&mut con)
+            .unwrap_or_else(|e| panic!("{} failed: {e}", command[0]));
+    }
+}
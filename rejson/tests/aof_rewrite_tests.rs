@@ -0,0 +1,154 @@
+use common::{random_key, Ctx};
+use std::path::Path;
+use std::{fs, iter, thread, time};
+use test_context::test_context;
+
+mod common;
+
+fn wait_for_aof_rewrite(con: &mut redis::Connection) {
+    for _ in 0..100 {
+        let info = redis::cmd("INFO")
+            .arg("persistence")
+            .query::<String>(con)
+            .unwrap();
+        if info.contains("aof_rewrite_in_progress:0") && info.contains("aof_rewrite_scheduled:0") {
+            return;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    panic!("aof rewrite did not finish");
+}
+
+// Switches to a plain AOF, which rewrites it from the current dataset.
+fn rewrite_aof(con: &mut redis::Connection) {
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg("aof-use-rdb-preamble")
+        .arg("no")
+        .query::<()>(con)
+        .unwrap();
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg("appendonly")
+        .arg("yes")
+        .query::<()>(con)
+        .unwrap();
+    wait_for_aof_rewrite(con);
+}
+
+// Counts the commands with the given name in the AOF files below the data directory.
+fn count_aof_commands(dir: &Path, name: &str) -> usize {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .map(|path| {
+            if path.is_dir() {
+                count_aof_commands(&path, name)
+            } else if path.to_string_lossy().contains(".aof") {
+                fs::read(&path)
+                    .unwrap()
+                    .windows(name.len())
+                    .filter(|w| *w == name.as_bytes())
+                    .count()
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+#[test_context(Ctx)]
+#[test]
+fn keys_round_trip(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    // large enough for the rewrite to split it into several commands
+    let large_array = format!(
+        "[{}]",
+        iter::repeat_n(r#""abcdefghijklmnop""#, 100000)
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let large_object =
+        format!(r#"{{"small":1,"large":{large_array},"a\"b":{{"0":[{large_array}]}}}}"#);
+    let documents = [
+        "null".to_string(),
+        "true".to_string(),
+        "42".to_string(),
+        r#""a string""#.to_string(),
+        r#"{"a":[1,2.5,{"b":null}],"a\"b":{"0":"\n"}}"#.to_string(),
+        large_array,
+        large_object,
+    ];
+
+    let keys = documents
+        .iter()
+        .map(|document| {
+            let key = random_key(16);
+            redis::cmd("JSON.SET")
+                .arg(&key)
+                .arg("$")
+                .arg(document)
+                .query::<()>(&mut con)
+                .unwrap();
+            key
+        })
+        .collect::<Vec<_>>();
+
+    rewrite_aof(&mut con);
+
+    ctx.restart(&["--appendonly yes", "--aof-use-rdb-preamble no"]);
+    let mut con = ctx.connection();
+
+    for (key, document) in keys.iter().zip(documents.iter()) {
+        assert_eq!(
+            redis::cmd("JSON.GET")
+                .arg(key)
+                .query::<redis::Value>(&mut con)
+                .unwrap(),
+            redis::Value::Data(document.as_bytes().to_vec())
+        );
+    }
+}
+
+#[test_context(Ctx)]
+#[test]
+fn large_documents_are_batched(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+    // about 2 MiB of members and another 2 MiB of elements, the members containing nulls
+    // outside of arrays are written one by one
+    let members = (0..100000)
+        .map(|i| format!(r#""k{i}":[{i},null,"abc"]"#))
+        .collect::<Vec<_>>()
+        .join(",");
+    let elements = iter::repeat_n(r#""abcdefghijklmnop""#, 100000)
+        .collect::<Vec<_>>()
+        .join(",");
+    let document =
+        format!(r#"{{{members},"n":{{"a":null}},"arr":[{elements}],"m":{{"b":[null]}}}}"#);
+
+    redis::cmd("JSON.SET")
+        .arg(&key)
+        .arg("$")
+        .arg(&document)
+        .query::<()>(&mut con)
+        .unwrap();
+
+    rewrite_aof(&mut con);
+
+    assert!(count_aof_commands(ctx.dir(), "JSON.MERGE") <= 4);
+    assert!(count_aof_commands(ctx.dir(), "JSON.ARRAPPEND") <= 4);
+
+    ctx.restart(&["--appendonly yes", "--aof-use-rdb-preamble no"]);
+    let mut con = ctx.connection();
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(&key)
+            .query::<redis::Value>(&mut con)
+            .unwrap(),
+        redis::Value::Data(document.into_bytes())
+    );
+}
@@ -2,9 +2,10 @@ extern crate redis;
 
 use rand::{distributions::Alphanumeric, distributions::Uniform, Rng};
 use std::env;
-use std::iter::repeat;
+use std::fs;
 use std::net::TcpListener;
-use std::process::{Child, Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::sync::Mutex;
 use std::{thread, time};
 use test_context::TestContext;
@@ -12,6 +13,8 @@ use test_context::TestContext;
 pub struct Ctx {
     redis: Child,
     client: redis::Client,
+    port: u16,
+    dir: PathBuf,
 }
 
 impl Ctx {
@@ -20,39 +23,92 @@ impl Ctx {
             .get_connection()
             .expect("failed to get connection")
     }
+
+    // The port of the server, e.g. for replicas to connect to.
+    #[allow(dead_code)]
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    // The data directory of the server, i.e. where it loads and saves dump.rdb.
+    #[allow(dead_code)]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Kill the server and start a new one on the same port and data directory, the additional
+    // arguments are passed to the new server which loads whatever the old one persisted.
+    #[allow(dead_code)]
+    pub fn restart(&mut self, args: &[&str]) {
+        self.try_restart(&[], args)
+            .expect("redis exited during startup");
+    }
+
+    // Like restart but also passes arguments to the module and fails with the exit status of the
+    // new server in case it exits during startup.
+    #[allow(dead_code)]
+    pub fn try_restart(&mut self, module_args: &[&str], args: &[&str]) -> Result<(), ExitStatus> {
+        stop_redis(&mut self.redis);
+        self.redis = start_redis(self.port, &self.dir, module_args, args);
+        wait_for_redis(&mut self.redis, &self.client)
+    }
 }
 
 impl TestContext for Ctx {
     fn setup() -> Ctx {
         let port = random_port();
-        let module = env::var("REDIS_JSON_MODULE").expect("REDIS_JSON_MODULE not set");
-        let ctx = Ctx {
-            redis: Command::new("redis-server")
-                .arg("--save \"\"")
-                .arg(format!("--port {port}"))
-                .arg(format!("--loadmodule {module}"))
-                .stdout(Stdio::null())
-                .spawn()
-                .expect("failed to start redis"),
+        let dir = env::temp_dir().join(format!("json-for-redis-{}", random_key(16)));
+        fs::create_dir_all(&dir).expect("failed to create data directory");
+        let mut ctx = Ctx {
+            redis: start_redis(port, &dir, &[], &[]),
             client: redis::Client::open(format!("redis://0.0.0.0:{port}/"))
                 .expect("failed to create client"),
+            port,
+            dir,
         };
-
-        repeat(())
-            .take(10)
-            .find(|_| {
-                thread::sleep(time::Duration::from_millis(100));
-                ctx.client.get_connection().is_ok()
-            })
-            .expect("failed to connect to redis");
-
+        wait_for_redis(&mut ctx.redis, &ctx.client).expect("redis exited during startup");
         ctx
     }
 
     fn teardown(mut self) {
-        self.redis.kill().expect("killing redis failed");
-        self.redis.wait().expect("waiting redis failed");
+        stop_redis(&mut self.redis);
+        fs::remove_dir_all(&self.dir).expect("removing data directory failed");
+    }
+}
+
+fn start_redis(port: u16, dir: &Path, module_args: &[&str], args: &[&str]) -> Child {
+    let module = env::var("REDIS_JSON_MODULE").expect("REDIS_JSON_MODULE not set");
+    Command::new("redis-server")
+        .arg("--save \"\"")
+        .arg(format!("--port {port}"))
+        .arg(format!("--dir {}", dir.display()))
+        .arg("--enable-debug-command local")
+        .arg(format!("--loadmodule {module} {}", module_args.join(" ")))
+        .args(args)
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("failed to start redis")
+}
+
+// The server may already have exited, e.g. after failing to load its data.
+fn stop_redis(redis: &mut Child) {
+    if redis.try_wait().expect("waiting redis failed").is_none() {
+        redis.kill().expect("killing redis failed");
+        redis.wait().expect("waiting redis failed");
+    }
+}
+
+fn wait_for_redis(redis: &mut Child, client: &redis::Client) -> Result<(), ExitStatus> {
+    for _ in 0..10 {
+        thread::sleep(time::Duration::from_millis(100));
+        if let Some(status) = redis.try_wait().expect("waiting redis failed") {
+            return Err(status);
+        }
+        if client.get_connection().is_ok() {
+            return Ok(());
+        }
     }
+    panic!("failed to connect to redis");
 }
 
 fn random_port() -> u16 {
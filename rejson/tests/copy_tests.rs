@@ -0,0 +1,141 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+fn json_get(con: &mut redis::Connection, key: &str) -> redis::Value {
+    redis::cmd("JSON.GET")
+        .arg(key)
+        .query::<redis::Value>(con)
+        .expect("json get failed")
+}
+
+#[test_context(Ctx)]
+#[test]
+fn copy_to_new_key(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let src = random_key(16);
+    let dst = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(src.clone())
+        .arg("$")
+        .arg(r#"{"a":[1,2],"b":"c"}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("COPY")
+            .arg(src.clone())
+            .arg(dst.clone())
+            .query::<redis::Value>(&mut con)
+            .expect("copy failed"),
+        redis::Value::Int(1)
+    );
+
+    // the copy must not share any data with its source
+    redis::cmd("JSON.ARRAPPEND")
+        .arg(src.clone())
+        .arg("$.a")
+        .arg("3")
+        .execute(&mut con);
+
+    assert_eq!(
+        json_get(&mut con, &src),
+        redis::Value::Data(r#"{"a":[1,2,3],"b":"c"}"#.as_bytes().to_vec())
+    );
+    assert_eq!(
+        json_get(&mut con, &dst),
+        redis::Value::Data(r#"{"a":[1,2],"b":"c"}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn copy_to_existing_key(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let src = random_key(16);
+    let dst = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(src.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+    redis::cmd("JSON.SET")
+        .arg(dst.clone())
+        .arg("$")
+        .arg(r#"{"b":2}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("COPY")
+            .arg(src.clone())
+            .arg(dst.clone())
+            .query::<redis::Value>(&mut con)
+            .expect("copy failed"),
+        redis::Value::Int(0)
+    );
+    assert_eq!(
+        json_get(&mut con, &dst),
+        redis::Value::Data(r#"{"b":2}"#.as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("COPY")
+            .arg(src)
+            .arg(dst.clone())
+            .arg("REPLACE")
+            .query::<redis::Value>(&mut con)
+            .expect("copy failed"),
+        redis::Value::Int(1)
+    );
+    assert_eq!(
+        json_get(&mut con, &dst),
+        redis::Value::Data(r#"{"a":1}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn copy_to_other_database(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("COPY")
+            .arg(key.clone())
+            .arg(key.clone())
+            .arg("DB")
+            .arg(1)
+            .query::<redis::Value>(&mut con)
+            .expect("copy failed"),
+        redis::Value::Int(1)
+    );
+
+    redis::cmd("SELECT").arg(1).execute(&mut con);
+    assert_eq!(
+        json_get(&mut con, &key),
+        redis::Value::Data(r#"{"a":1}"#.as_bytes().to_vec())
+    );
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"b":2}"#)
+        .execute(&mut con);
+
+    redis::cmd("SELECT").arg(0).execute(&mut con);
+    assert_eq!(
+        json_get(&mut con, &key),
+        redis::Value::Data(r#"{"a":1}"#.as_bytes().to_vec())
+    );
+}
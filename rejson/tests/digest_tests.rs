@@ -0,0 +1,98 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+fn digest(con: &mut redis::Connection, json: &str) -> String {
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(json)
+        .execute(con);
+
+    redis::cmd("DEBUG")
+        .arg("DIGEST-VALUE")
+        .arg(key)
+        .query::<Vec<String>>(con)
+        .expect("debug digest-value failed")
+        .remove(0)
+}
+
+#[test_context(Ctx)]
+#[test]
+fn same_document(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let json = r#"{"a":[1,"b",null],"c":{"d":true}}"#;
+
+    assert_eq!(digest(&mut con, json), digest(&mut con, json));
+}
+
+#[test_context(Ctx)]
+#[test]
+fn member_order_is_ignored(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    assert_eq!(
+        digest(&mut con, r#"{"a":1,"b":{"c":2,"d":3}}"#),
+        digest(&mut con, r#"{"b":{"d":3,"c":2},"a":1}"#)
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn different_documents(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let digests = [
+        r#"{"a":1}"#,
+        r#"{"a":"1"}"#,
+        r#"{"a":1.0}"#,
+        r#"{"b":1}"#,
+        "[1,2]",
+        "[2,1]",
+        "null",
+    ]
+    .map(|json| digest(&mut con, json));
+
+    for (i, a) in digests.iter().enumerate() {
+        for b in digests.iter().skip(i + 1) {
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[test_context(Ctx)]
+#[test]
+fn digest_of_keyspace(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":2}"#)
+        .execute(&mut con);
+
+    let before = redis::cmd("DEBUG")
+        .arg("DIGEST")
+        .query::<String>(&mut con)
+        .expect("debug digest failed");
+
+    redis::cmd("JSON.SET")
+        .arg(key)
+        .arg("$")
+        .arg(r#"{"b":2,"a":1}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("DEBUG")
+            .arg("DIGEST")
+            .query::<String>(&mut con)
+            .expect("debug digest failed"),
+        before
+    );
+}
@@ -0,0 +1,31 @@
+#!/bin/sh
+# Saves the RDB files of upstream RedisJSON releases loaded by rdb_tests.rs, which need to be
+# produced by upstream itself to catch misreadings of its encodings. Requires docker, the images
+# are passed as arguments, e.g. a 1.0 release for the tree encoding of version 0 and a 2.x release
+# for the string encoding of version 3:
+#
+#   ./generate.sh redislabs/rejson:1.0.8 redislabs/rejson:2.6.6
+set -eu
+
+cd "$(dirname "$0")"
+
+save() {
+    image=$1
+    out=$2
+    container=$(docker run -d "$image")
+    trap 'docker rm -f "$container" >/dev/null' EXIT
+    until docker exec "$container" redis-cli ping >/dev/null 2>&1; do
+        sleep 0.1
+    done
+    # the legacy root path works with every release
+    docker exec "$container" redis-cli JSON.SET null . 'null'
+    docker exec "$container" redis-cli JSON.SET scalars . '["a☺",2.5,-3,true,false,null]'
+    docker exec "$container" redis-cli JSON.SET nested . '{"b":{},"a":[[]]}'
+    docker exec "$container" redis-cli SAVE
+    docker cp "$container:/data/dump.rdb" "$out"
+    docker rm -f "$container" >/dev/null
+    trap - EXIT
+}
+
+save "$1" upstream-v1.rdb
+save "$2" upstream-v2.rdb
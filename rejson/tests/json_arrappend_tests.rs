@@ -0,0 +1,141 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn bad_args_wrong_arity_no_value(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[]")
+        .execute(&mut con);
+
+    redis::cmd("JSON.ARRAPPEND")
+        .arg(key)
+        .arg("$")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrappend should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.ARRAPPEND")
+        .arg(key)
+        .arg("$")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrappend should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn append_at_root(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[1]")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRAPPEND")
+            .arg(key.clone())
+            .arg("$")
+            .arg("2")
+            .arg(r#""3""#)
+            .query::<redis::Value>(&mut con)
+            .expect("json arrappend failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(3)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[1,2,"3"]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn append_to_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[1],"b":{"c":1},"d":[1,2]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRAPPEND")
+            .arg(key.clone())
+            .arg("$.*")
+            .arg("3")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrappend failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(2),
+            redis::Value::Nil,
+            redis::Value::Int(3)
+        ])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":[1,3],"b":{"c":1},"d":[1,2,3]}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn append_below_changed_array(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[{"a":[1]}]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRAPPEND")
+            .arg(key.clone())
+            .arg("$..a")
+            .arg("9")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrappend failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(2), redis::Value::Int(2)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":[{"a":[1,9]},9]}"#.as_bytes().to_vec())
+    );
+}
@@ -0,0 +1,119 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINDEX")
+            .arg(key)
+            .arg("$")
+            .arg("1")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrindex failed"),
+        redis::Value::Nil
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn bad_args_wrong_arity_no_value(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[]")
+        .execute(&mut con);
+
+    redis::cmd("JSON.ARRINDEX")
+        .arg(key)
+        .arg("$")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrindex should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn index_of_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[1,2,3,2],"b":{"c":2},"d":[1.0,2.0]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINDEX")
+            .arg(key)
+            .arg("$.*")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrindex failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(1),
+            redis::Value::Nil,
+            redis::Value::Int(-1)
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn index_in_range(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"["a","b","a","b"]"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINDEX")
+            .arg(key.clone())
+            .arg("$")
+            .arg(r#""a""#)
+            .arg("1")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrindex failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(2)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINDEX")
+            .arg(key.clone())
+            .arg("$")
+            .arg(r#""a""#)
+            .arg("1")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrindex failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(-1)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINDEX")
+            .arg(key)
+            .arg("$")
+            .arg(r#""b""#)
+            .arg("-2")
+            .arg("0")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrindex failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(3)])
+    );
+}
@@ -0,0 +1,167 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn bad_args_wrong_arity_no_value(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[]")
+        .execute(&mut con);
+
+    redis::cmd("JSON.ARRINSERT")
+        .arg(key)
+        .arg("$")
+        .arg("0")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrinsert should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn insert_at_index(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[1,4],"b":"c"}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINSERT")
+            .arg(key.clone())
+            .arg("$.*")
+            .arg("1")
+            .arg("2")
+            .arg("3")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrinsert failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(4), redis::Value::Nil])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$.a")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[[1,2,3,4]]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn insert_at_negative_index(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[1,3]")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINSERT")
+            .arg(key.clone())
+            .arg("$")
+            .arg("-1")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrinsert failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(3)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[1,2,3]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn index_out_of_bounds(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[1]")
+        .execute(&mut con);
+
+    redis::cmd("JSON.ARRINSERT")
+        .arg(key.clone())
+        .arg("$")
+        .arg("2")
+        .arg("2")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrinsert should have failed");
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[1]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn recursive_path_inserts_into_nested_arrays(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"x":[[1,2],[3,4]],"y":[5,6]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRINSERT")
+            .arg(key.clone())
+            .arg("$..*")
+            .arg("-2")
+            .arg("[]")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrinsert failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(3),
+            redis::Value::Int(3),
+            redis::Value::Nil,
+            redis::Value::Nil,
+            redis::Value::Int(3),
+            redis::Value::Int(3),
+            redis::Value::Nil,
+            redis::Value::Nil,
+            redis::Value::Nil,
+            redis::Value::Nil
+        ])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"x":[[],[[],1,2],[[],3,4]],"y":[[],5,6]}"#.as_bytes().to_vec())
+    );
+}
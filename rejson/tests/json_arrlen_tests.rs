@@ -0,0 +1,69 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRLEN")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json arrlen failed"),
+        redis::Value::Nil
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn length_at_root(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[1,2,3]")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRLEN")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json arrlen failed"),
+        redis::Value::Int(3)
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn length_of_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[1],"b":{"c":1},"d":[]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRLEN")
+            .arg(key)
+            .arg("$.*")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrlen failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(1),
+            redis::Value::Nil,
+            redis::Value::Int(0)
+        ])
+    );
+}
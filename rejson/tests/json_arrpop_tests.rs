@@ -0,0 +1,153 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.ARRPOP")
+        .arg(key)
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrpop should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn pop_last_at_root(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"[1,{"a":"b"}]"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRPOP")
+            .arg(key.clone())
+            .query::<redis::Value>(&mut con)
+            .expect("json arrpop failed"),
+        redis::Value::Data(r#"{"a":"b"}"#.as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[1]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[3],"nested":{"a":[3,4]},"nested2":{"a":[]},"nested3":{"a":42}}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRPOP")
+            .arg(key.clone())
+            .arg("$.*.a")
+            .arg("0")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrpop failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("3".as_bytes().to_vec()),
+            redis::Value::Nil,
+            redis::Value::Nil
+        ])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$.*.a")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[[4],[],42]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn index_out_of_range_is_clamped(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[1,2,3]")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRPOP")
+            .arg(key.clone())
+            .arg("$")
+            .arg("-10")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrpop failed"),
+        redis::Value::Bulk(vec![redis::Value::Data("1".as_bytes().to_vec())])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.ARRPOP")
+            .arg(key.clone())
+            .arg("$")
+            .arg("10")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrpop failed"),
+        redis::Value::Bulk(vec![redis::Value::Data("3".as_bytes().to_vec())])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn pop_from_nested_arrays(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[{"a":[1,2]},3]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRPOP")
+            .arg(key.clone())
+            .arg("$..a")
+            .arg("0")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrpop failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data(r#"{"a":[2]}"#.as_bytes().to_vec()),
+            redis::Value::Data("1".as_bytes().to_vec())
+        ])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":[3]}"#.as_bytes().to_vec())
+    );
+}
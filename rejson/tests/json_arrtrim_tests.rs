@@ -0,0 +1,91 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.ARRTRIM")
+        .arg(key)
+        .arg("$")
+        .arg("0")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json arrtrim should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn trim_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[1,2,3,4],"b":{"c":1},"d":[1]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRTRIM")
+            .arg(key.clone())
+            .arg("$.*")
+            .arg("1")
+            .arg("-2")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrtrim failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(2),
+            redis::Value::Nil,
+            redis::Value::Int(0)
+        ])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":[2,3],"b":{"c":1},"d":[]}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn stop_out_of_range_is_clamped(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[1,2,3]")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.ARRTRIM")
+            .arg(key.clone())
+            .arg("$")
+            .arg("1")
+            .arg("99")
+            .query::<redis::Value>(&mut con)
+            .expect("json arrtrim failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(2)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[2,3]"#.as_bytes().to_vec())
+    );
+}
@@ -0,0 +1,112 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+fn memory(con: &mut redis::Connection, key: &str, path: &str) -> redis::Value {
+    redis::cmd("JSON.DEBUG")
+        .arg("MEMORY")
+        .arg(key)
+        .arg(path)
+        .query::<redis::Value>(con)
+        .expect("json debug memory failed")
+}
+
+#[test_context(Ctx)]
+#[test]
+fn bad_args_unknown_subcommand(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    redis::cmd("JSON.DEBUG")
+        .arg("FOO")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json debug should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn memory_key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(memory(&mut con, &key, "."), redis::Value::Int(0));
+    assert_eq!(memory(&mut con, &key, "$"), redis::Value::Bulk(vec![]));
+}
+
+#[test_context(Ctx)]
+#[test]
+fn memory_of_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":"a long string that is stored on the heap","c":[1,2,3]}"#)
+        .execute(&mut con);
+
+    let sizes = match memory(&mut con, &key, "$.*") {
+        redis::Value::Bulk(v) => v
+            .into_iter()
+            .map(|v| match v {
+                redis::Value::Int(i) => i,
+                v => panic!("unexpected reply {v:?}"),
+            })
+            .collect::<Vec<_>>(),
+        v => panic!("unexpected reply {v:?}"),
+    };
+    assert_eq!(sizes.len(), 3);
+    assert!(sizes[0] > 0);
+    assert!(sizes[1] > sizes[0]);
+    assert!(sizes[2] > sizes[0]);
+
+    let total = match memory(&mut con, &key, ".") {
+        redis::Value::Int(i) => i,
+        v => panic!("unexpected reply {v:?}"),
+    };
+    assert!(total > sizes.iter().sum());
+}
+
+#[test_context(Ctx)]
+#[test]
+fn memory_usage_of_key(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(format!(r#"["{}"]"#, "a".repeat(10000)))
+        .execute(&mut con);
+
+    let usage = redis::cmd("MEMORY")
+        .arg("USAGE")
+        .arg(key)
+        .query::<i64>(&mut con)
+        .expect("memory usage failed");
+    assert!(usage > 10000);
+}
+
+#[test_context(Ctx)]
+#[test]
+fn legacy_path_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.DEBUG")
+        .arg("MEMORY")
+        .arg(key)
+        .arg(".b")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json debug memory should have failed");
+}
@@ -122,3 +122,34 @@ fn bad_args(ctx: &mut Ctx) {
         .query::<redis::Value>(&mut con)
         .expect_err("json del should have failed");
 }
+
+#[test_context(Ctx)]
+#[test]
+fn union_repeating_an_index(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"["a","b","c"]"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.DEL")
+            .arg(key.clone())
+            .arg("$[0,1,0]")
+            .query::<redis::Value>(&mut con)
+            .expect("json del failed"),
+        redis::Value::Int(2)
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"["c"]"#.as_bytes().to_vec())
+    );
+}
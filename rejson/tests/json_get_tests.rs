@@ -56,6 +56,52 @@ fn recursive_decent(ctx: &mut Ctx) {
     ));
 }
 
+#[test_context(Ctx)]
+#[test]
+fn filter(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"items":[{"name":"a","price":5},{"name":"b","price":15},{"name":"c","price":9.5}]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$.items[?(@.price < 10)].name")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"["a","c"]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn json_pointer(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a/b":[1,{"0":2}],"c":3}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("/a~1b/1/0")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[2]"#.as_bytes().to_vec())
+    );
+}
+
 #[test_context(Ctx)]
 #[test]
 fn no_value_matched_at_path(ctx: &mut Ctx) {
@@ -299,3 +345,51 @@ fn format_empty_object(ctx: &mut Ctx) {
         redis::Value::Data(r#"{}"#.as_bytes().to_vec())
     );
 }
+
+#[test_context(Ctx)]
+#[test]
+fn legacy_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{"b":[1,2]},"c":"d"}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key.clone())
+            .arg("a.b[1]")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"2"#.as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key.clone())
+            .arg(".")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":{"b":[1,2]},"c":"d"}"#.as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key.clone())
+            .arg(".c")
+            .arg(".a")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{".c":"d",".a":{"b":[1,2]}}"#.as_bytes().to_vec())
+    );
+
+    redis::cmd("JSON.GET")
+        .arg(key)
+        .arg(".e")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json get should have failed");
+}
@@ -0,0 +1,188 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn create_key_at_root(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":null}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":1}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn create_key_not_at_root(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.MERGE")
+        .arg(key)
+        .arg("$.a")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json merge should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":2}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$.a")
+        .arg("3")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key.clone())
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":3}]"#.as_bytes().to_vec())
+    );
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$.b")
+        .arg("8")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key.clone())
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":3,"b":8}]"#.as_bytes().to_vec())
+    );
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$.a")
+        .arg("null")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"b":8}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn merge_nested_objects(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{"b":1,"c":[1]},"d":{"b":2}}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$.*")
+        .arg(r#"{"b":null,"c":[2],"e":{"f":null}}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":{"c":[2],"e":{}},"d":{"c":[2],"e":{}}}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn null_patch_deletes_members(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":{"a":2},"arr":[1,2]}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$..a")
+        .arg("null")
+        .execute(&mut con);
+
+    for path in ["$", "$.arr[0]"] {
+        redis::cmd("JSON.MERGE")
+            .arg(key.clone())
+            .arg(path)
+            .arg("null")
+            .query::<redis::Value>(&mut con)
+            .expect_err("json merge should have failed");
+    }
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"b":{},"arr":[1,2]}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn create_key_with_null_patch(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.MERGE")
+        .arg(key.clone())
+        .arg("$")
+        .arg("null")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json merge should have failed");
+
+    assert_eq!(
+        redis::cmd("EXISTS")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("exists failed"),
+        redis::Value::Int(0)
+    );
+}
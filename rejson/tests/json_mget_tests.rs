@@ -0,0 +1,118 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn bad_args_wrong_arity_no_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.MGET")
+        .arg(key)
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mget should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key1 = random_key(16);
+    let key2 = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key1.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":2,"nested":{"a":3},"c":null}"#)
+        .execute(&mut con);
+    redis::cmd("JSON.SET")
+        .arg(key2.clone())
+        .arg("$")
+        .arg(r#"{"a":4,"b":5,"nested":{"a":6},"c":null}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.MGET")
+            .arg(key1)
+            .arg(key2)
+            .arg("$.*.a")
+            .query::<redis::Value>(&mut con)
+            .expect("json mget failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("[3]".as_bytes().to_vec()),
+            redis::Value::Data("[6]".as_bytes().to_vec()),
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn missing_and_non_json_keys(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key1 = random_key(16);
+    let key2 = random_key(16);
+    let key3 = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key1.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+    redis::cmd("SET")
+        .arg(key3.clone())
+        .arg("foo")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.MGET")
+            .arg(key1)
+            .arg(key2)
+            .arg(key3)
+            .arg("$.a")
+            .query::<redis::Value>(&mut con)
+            .expect("json mget failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("[1]".as_bytes().to_vec()),
+            redis::Value::Nil,
+            redis::Value::Nil,
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn legacy_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key1 = random_key(16);
+    let key2 = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key1.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+    redis::cmd("JSON.SET")
+        .arg(key2.clone())
+        .arg("$")
+        .arg(r#"{"b":2}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.MGET")
+            .arg(key1)
+            .arg(key2)
+            .arg(".a")
+            .query::<redis::Value>(&mut con)
+            .expect("json mget failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("1".as_bytes().to_vec()),
+            redis::Value::Nil,
+        ])
+    );
+}
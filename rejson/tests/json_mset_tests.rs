@@ -0,0 +1,180 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn bad_args_wrong_arity(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.MSET")
+        .arg(key)
+        .arg("$")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mset should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key1 = random_key(16);
+    let key2 = random_key(16);
+    let key3 = random_key(16);
+
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$")
+        .arg(r#"{"a":2}"#)
+        .arg(key2.clone())
+        .arg("$")
+        .arg(r#"{"f":{"a":2}}"#)
+        .arg(key3.clone())
+        .arg("$")
+        .arg(r#"{"f1":{"a":0},"f2":{"a":0}}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$.a")
+        .arg("3")
+        .arg(key2.clone())
+        .arg("$.f.a")
+        .arg("4")
+        .arg(key3.clone())
+        .arg("$..a")
+        .arg("5")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.MGET")
+            .arg(key1)
+            .arg(key2)
+            .arg(key3)
+            .arg("$..a")
+            .query::<redis::Value>(&mut con)
+            .expect("json mget failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("[3]".as_bytes().to_vec()),
+            redis::Value::Data("[4]".as_bytes().to_vec()),
+            redis::Value::Data("[5,5]".as_bytes().to_vec()),
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn same_key_is_applied_in_order(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.MSET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .arg(key.clone())
+        .arg("$.b")
+        .arg("2")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":1,"b":2}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn nothing_is_written_on_error(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key1 = random_key(16);
+    let key2 = random_key(16);
+    let key3 = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key1.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+
+    // invalid json
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$.a")
+        .arg("2")
+        .arg(key2.clone())
+        .arg("$")
+        .arg("{")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mset should have failed");
+
+    // invalid path
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$.a")
+        .arg("2")
+        .arg(key2.clone())
+        .arg("$[")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mset should have failed");
+
+    // new key not at the root
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$.a")
+        .arg("2")
+        .arg(key3.clone())
+        .arg("$.a")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mset should have failed");
+
+    // too many matches after an earlier triple for the same key was applied
+    let many = format!("[{}]", vec![r#"{"x":{}}"#; 1001].join(","));
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$.a")
+        .arg("2")
+        .arg(key1.clone())
+        .arg("$")
+        .arg(&many)
+        .arg(key1.clone())
+        .arg("$[*].x.y")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mset should have failed");
+
+    // too many matches in a key after a different key
+    redis::cmd("JSON.SET")
+        .arg(key2.clone())
+        .arg("$")
+        .arg(&many)
+        .execute(&mut con);
+    redis::cmd("JSON.MSET")
+        .arg(key1.clone())
+        .arg("$.a")
+        .arg("2")
+        .arg(key2.clone())
+        .arg("$[*].x.y")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json mset should have failed");
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key1)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":1}"#.as_bytes().to_vec())
+    );
+}
@@ -0,0 +1,271 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.NUMINCRBY")
+        .arg(key)
+        .arg("$")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json numincrby should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn not_a_number(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("1")
+        .execute(&mut con);
+
+    redis::cmd("JSON.NUMINCRBY")
+        .arg(key)
+        .arg("$")
+        .arg(r#""1""#)
+        .query::<redis::Value>(&mut con)
+        .expect_err("json numincrby should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":"b","b":[{"a":2},{"a":5},{"a":"c"}]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg("$.a")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[null]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key)
+            .arg("$.b[*].a")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[4,7,null]".as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn integers_and_floats(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":1.5,"c":1}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg("$.a")
+            .arg("1")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[2]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg("$.b")
+            .arg("1")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[2.5]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg("$.c")
+            .arg("0.5")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[1.5]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.TYPE")
+            .arg(key)
+            .arg("$.*")
+            .query::<redis::Value>(&mut con)
+            .expect("json type failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("integer".as_bytes().to_vec()),
+            redis::Value::Data("number".as_bytes().to_vec()),
+            redis::Value::Data("number".as_bytes().to_vec()),
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn overflow(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":9223372036854775807,"b":1}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.NUMINCRBY")
+        .arg(key.clone())
+        .arg("$.*")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json numincrby should have failed");
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$.b")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data("[1]".as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn integers_above_i64(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":18446744073709551615}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.NUMINCRBY")
+        .arg(key.clone())
+        .arg("$.a")
+        .arg("1")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json numincrby should have failed");
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg("$.a")
+            .arg("-9223372036854775808")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[9223372036854775807]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.TYPE")
+            .arg(key)
+            .arg("$.a")
+            .query::<redis::Value>(&mut con)
+            .expect("json type failed"),
+        redis::Value::Bulk(vec![redis::Value::Data("integer".as_bytes().to_vec())])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn legacy_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":"c"}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg(".a")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("3".as_bytes().to_vec())
+    );
+
+    redis::cmd("JSON.NUMINCRBY")
+        .arg(key.clone())
+        .arg(".b")
+        .arg("2")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json numincrby should have failed");
+
+    redis::cmd("JSON.NUMINCRBY")
+        .arg(key)
+        .arg(".d")
+        .arg("2")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json numincrby should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn union_repeating_an_index(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg("[9223372036854775806,1]")
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.NUMINCRBY")
+            .arg(key.clone())
+            .arg("$[0,1,0]")
+            .arg("1")
+            .query::<redis::Value>(&mut con)
+            .expect("json numincrby failed"),
+        redis::Value::Data("[9223372036854775807,2]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data("[9223372036854775807,2]".as_bytes().to_vec())
+    );
+}
@@ -0,0 +1,83 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":"b","b":[{"a":2},{"a":5},{"a":"c"}]}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.NUMMULTBY")
+            .arg(key.clone())
+            .arg("$.a")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json nummultby failed"),
+        redis::Value::Data("[null]".as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.NUMMULTBY")
+            .arg(key)
+            .arg("$.b[*].a")
+            .arg("2")
+            .query::<redis::Value>(&mut con)
+            .expect("json nummultby failed"),
+        redis::Value::Data("[4,10,null]".as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn multiply_by_float(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":3}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.NUMMULTBY")
+            .arg(key.clone())
+            .arg("$.a")
+            .arg("0.5")
+            .query::<redis::Value>(&mut con)
+            .expect("json nummultby failed"),
+        redis::Value::Data("[1.5]".as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn overflow(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":4611686018427387904}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.NUMMULTBY")
+        .arg(key)
+        .arg("$.a")
+        .arg("2")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json nummultby should have failed");
+}
@@ -0,0 +1,75 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(
+        redis::cmd("JSON.OBJKEYS")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json objkeys failed"),
+        redis::Value::Nil
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn keys_in_document_order(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"c":1,"a":2,"b":3}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.OBJKEYS")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json objkeys failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Data("c".as_bytes().to_vec()),
+            redis::Value::Data("a".as_bytes().to_vec()),
+            redis::Value::Data("b".as_bytes().to_vec()),
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":[3],"nested":{"a":{"b":2,"c":1}}}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.OBJKEYS")
+            .arg(key)
+            .arg("$..a")
+            .query::<redis::Value>(&mut con)
+            .expect("json objkeys failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Nil,
+            redis::Value::Bulk(vec![
+                redis::Value::Data("b".as_bytes().to_vec()),
+                redis::Value::Data("c".as_bytes().to_vec()),
+            ]),
+        ])
+    );
+}
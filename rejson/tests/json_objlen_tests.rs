@@ -0,0 +1,47 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(
+        redis::cmd("JSON.OBJLEN")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json objlen failed"),
+        redis::Value::Nil
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn length_of_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{},"b":[1,2],"c":{"d":1,"e":2}}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.OBJLEN")
+            .arg(key)
+            .arg("$.*")
+            .query::<redis::Value>(&mut con)
+            .expect("json objlen failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(0),
+            redis::Value::Nil,
+            redis::Value::Int(2)
+        ])
+    );
+}
@@ -0,0 +1,122 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.PATCH")
+        .arg(key)
+        .arg(r#"[{"op":"add","path":"/a","value":1}]"#)
+        .query::<redis::Value>(&mut con)
+        .expect_err("json patch should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn apply_operations_in_order(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{"b":[1,2]},"c":"d"}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.PATCH")
+        .arg(key.clone())
+        .arg(
+            r#"[
+                {"op":"test","path":"/c","value":"d"},
+                {"op":"add","path":"/a/b/-","value":3},
+                {"op":"remove","path":"/a/b/0"},
+                {"op":"replace","path":"/c","value":"e"},
+                {"op":"copy","from":"/a/b","path":"/f"},
+                {"op":"move","from":"/c","path":"/a/c"}
+            ]"#,
+        )
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":{"b":[2,3],"c":"e"},"f":[2,3]}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn failed_test_rejects_patch(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.PATCH")
+        .arg(key.clone())
+        .arg(
+            r#"[
+                {"op":"replace","path":"/a","value":2},
+                {"op":"test","path":"/a","value":1}
+            ]"#,
+        )
+        .query::<redis::Value>(&mut con)
+        .expect_err("json patch should have failed");
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":1}"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn failed_operation_undoes_earlier_ones(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1,"b":[1,2],"c":{"d":2}}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.PATCH")
+        .arg(key.clone())
+        .arg(
+            r#"[
+                {"op":"remove","path":"/a"},
+                {"op":"move","from":"/b/0","path":"/c/d"},
+                {"op":"add","path":"/b/-","value":3},
+                {"op":"remove","path":"/e"}
+            ]"#,
+        )
+        .query::<redis::Value>(&mut con)
+        .expect_err("json patch should have failed");
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"{"a":1,"b":[1,2],"c":{"d":2}}"#.as_bytes().to_vec())
+    );
+}
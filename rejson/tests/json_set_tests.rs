@@ -166,6 +166,41 @@ fn adding_new_key_to_object(ctx: &mut Ctx) {
     );
 }
 
+#[test_context(Ctx)]
+#[test]
+fn adding_new_key_to_object_json_pointer(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{"b":[1,2]}}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("/a/c")
+        .arg(r#"8"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("/a/b/1")
+        .arg(r#"3"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":{"b":[1,3],"c":8}}]"#.as_bytes().to_vec())
+    );
+}
+
 #[test_context(Ctx)]
 #[test]
 fn adding_new_key_to_object_recursive_decent(ctx: &mut Ctx) {
@@ -312,3 +347,88 @@ fn xx_key_does_not_exist(ctx: &mut Ctx) {
         redis::Value::Nil
     );
 }
+
+#[test_context(Ctx)]
+#[test]
+fn create_missing_objects(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{}}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.SET")
+            .arg(key.clone())
+            .arg("$.a.b.c")
+            .arg("1")
+            .arg("CREATE")
+            .query::<redis::Value>(&mut con)
+            .expect("json set failed"),
+        redis::Value::Okay
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":{"b":{"c":1}}}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn create_missing_objects_in_new_key(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(
+        redis::cmd("JSON.SET")
+            .arg(key.clone())
+            .arg("$.a.b")
+            .arg("1")
+            .arg("NX")
+            .arg("CREATE")
+            .query::<redis::Value>(&mut con)
+            .expect("json set failed"),
+        redis::Value::Okay
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":{"b":1}}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn create_along_indefinite_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":{}}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.SET")
+        .arg(key)
+        .arg("$..a.b")
+        .arg("1")
+        .arg("CREATE")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json set should have failed");
+}
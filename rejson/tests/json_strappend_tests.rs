@@ -0,0 +1,104 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.STRAPPEND")
+        .arg(key)
+        .arg("$")
+        .arg(r#""a""#)
+        .query::<redis::Value>(&mut con)
+        .expect_err("json strappend should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn not_a_string(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#""a""#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.STRAPPEND")
+        .arg(key)
+        .arg("$")
+        .arg("b")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json strappend should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn append_at_root_without_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#""foo""#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.STRAPPEND")
+            .arg(key.clone())
+            .arg(r#""bar""#)
+            .query::<redis::Value>(&mut con)
+            .expect("json strappend failed"),
+        redis::Value::Int(6)
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#""foobar""#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":"foo","nested":{"a":"hello"},"nested2":{"a":31}}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.STRAPPEND")
+            .arg(key.clone())
+            .arg("$.*.a")
+            .arg(r#""baz""#)
+            .query::<redis::Value>(&mut con)
+            .expect("json strappend failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(8), redis::Value::Nil])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$.*.a")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"["hellobaz",31]"#.as_bytes().to_vec())
+    );
+}
@@ -0,0 +1,89 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    assert_eq!(
+        redis::cmd("JSON.STRLEN")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json strlen failed"),
+        redis::Value::Nil
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn length_of_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":"foo","b":1,"c":""}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.STRLEN")
+            .arg(key)
+            .arg("$.*")
+            .query::<redis::Value>(&mut con)
+            .expect("json strlen failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(3),
+            redis::Value::Nil,
+            redis::Value::Int(0)
+        ])
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn length_of_multibyte_string(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#""a☺""#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.STRLEN")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("json strlen failed"),
+        redis::Value::Int(4)
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn legacy_path_wrong_type(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":1}"#)
+        .execute(&mut con);
+
+    redis::cmd("JSON.STRLEN")
+        .arg(key)
+        .arg(".a")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json strlen should have failed");
+}
@@ -0,0 +1,133 @@
+use common::{random_key, Ctx};
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn key_does_not_exist(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.TOGGLE")
+        .arg(key)
+        .arg("$")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json toggle should have failed");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn upstream_example(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"bool":true}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.TOGGLE")
+            .arg(key.clone())
+            .arg("$.bool")
+            .query::<redis::Value>(&mut con)
+            .expect("json toggle failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(0)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key.clone())
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"bool":false}]"#.as_bytes().to_vec())
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.TOGGLE")
+            .arg(key.clone())
+            .arg("$.bool")
+            .query::<redis::Value>(&mut con)
+            .expect("json toggle failed"),
+        redis::Value::Bulk(vec![redis::Value::Int(1)])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"bool":true}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn toggle_each_match(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"a":true,"b":1,"c":false}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.TOGGLE")
+            .arg(key.clone())
+            .arg("$.*")
+            .query::<redis::Value>(&mut con)
+            .expect("json toggle failed"),
+        redis::Value::Bulk(vec![
+            redis::Value::Int(0),
+            redis::Value::Nil,
+            redis::Value::Int(1)
+        ])
+    );
+
+    assert_eq!(
+        redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query::<redis::Value>(&mut con)
+            .expect("json get failed"),
+        redis::Value::Data(r#"[{"a":false,"b":1,"c":true}]"#.as_bytes().to_vec())
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn legacy_path(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let key = random_key(16);
+
+    redis::cmd("JSON.SET")
+        .arg(key.clone())
+        .arg("$")
+        .arg(r#"{"bool":true,"num":1}"#)
+        .execute(&mut con);
+
+    assert_eq!(
+        redis::cmd("JSON.TOGGLE")
+            .arg(key.clone())
+            .arg(".bool")
+            .query::<redis::Value>(&mut con)
+            .expect("json toggle failed"),
+        redis::Value::Data("false".as_bytes().to_vec())
+    );
+
+    redis::cmd("JSON.TOGGLE")
+        .arg(key)
+        .arg(".num")
+        .query::<redis::Value>(&mut con)
+        .expect_err("json toggle should have failed");
+}
@@ -0,0 +1,102 @@
+use common::{random_key, Ctx};
+use std::time;
+use test_context::test_context;
+
+mod common;
+
+#[test_context(Ctx)]
+#[test]
+fn event_per_write(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+    let mut sub = ctx.connection();
+
+    let key = random_key(16);
+    let other = random_key(16);
+
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg("notify-keyspace-events")
+        .arg("Kd")
+        .execute(&mut con);
+
+    let mut pubsub = sub.as_pubsub();
+    pubsub
+        .set_read_timeout(Some(time::Duration::from_secs(1)))
+        .unwrap();
+    pubsub
+        .subscribe(format!("__keyspace@0__:{key}"))
+        .expect("subscribe failed");
+
+    let commands: &[&[&str]] = &[
+        &["JSON.SET", &key, "$", r#"{"a":[1],"b":"c","d":1,"e":true}"#],
+        // neither reads nor writes that don't change anything emit events
+        &["JSON.GET", &key],
+        &["JSON.SET", &key, "$", "{}", "NX"],
+        &["JSON.SET", &other, "$", "{}", "XX"],
+        &["JSON.SET", &key, "$.x.y", "1"],
+        &["JSON.ARRAPPEND", &key, "$.b", "2"],
+        &["JSON.ARRINSERT", &key, "$.b", "0", "0"],
+        &["JSON.ARRPOP", &key, "$.b"],
+        &["JSON.ARRTRIM", &key, "$.b", "0", "0"],
+        &["JSON.ARRTRIM", &key, "$.a", "0", "-1"],
+        &["JSON.STRAPPEND", &key, "$.a", r#""d""#],
+        &["JSON.NUMINCRBY", &key, "$.b", "1"],
+        &["JSON.NUMMULTBY", &key, "$.b", "2"],
+        &["JSON.TOGGLE", &key, "$.d"],
+        &["JSON.MERGE", &key, "$.x.y", r#"{"f":1}"#],
+        &[
+            "JSON.PATCH",
+            &key,
+            r#"[{"op":"test","path":"/b","value":"c"}]"#,
+        ],
+        &["JSON.MSET", &key, "$.x.y", "1"],
+        // every other command writes something
+        &["JSON.ARRAPPEND", &key, "$.a", "2"],
+        &["JSON.ARRINSERT", &key, "$.a", "0", "0"],
+        &["JSON.ARRPOP", &key, "$.a"],
+        &["JSON.ARRTRIM", &key, "$.a", "0", "0"],
+        &["JSON.STRAPPEND", &key, "$.b", r#""d""#],
+        &["JSON.NUMINCRBY", &key, "$.d", "1"],
+        &["JSON.NUMMULTBY", &key, "$.d", "2"],
+        &["JSON.TOGGLE", &key, "$.e"],
+        &["JSON.MERGE", &key, "$", r#"{"f":1}"#],
+        &["JSON.PATCH", &key, r#"[{"op":"remove","path":"/f"}]"#],
+        &["JSON.MSET", &key, "$.g", "1", &other, "$", "2"],
+        &["JSON.CLEAR", &key, "$.a"],
+        &["JSON.CLEAR", &key, "$.a"],
+        &["JSON.DEL", &key, "$.g"],
+        &["JSON.DEL", &key, "$.g"],
+        &["JSON.FORGET", &key],
+    ];
+    for command in commands {
+        redis::cmd(command[0])
+            .arg(&command[1..])
+            .query::<redis::Value>(&mut con)
+            .unwrap_or_else(|e| panic!("{} failed: {e}", command[0]));
+    }
+
+    let events = [
+        "json.set",
+        "json.arrappend",
+        "json.arrinsert",
+        "json.arrpop",
+        "json.arrtrim",
+        "json.strappend",
+        "json.numincrby",
+        "json.nummultby",
+        "json.toggle",
+        "json.merge",
+        "json.patch",
+        "json.mset",
+        "json.clear",
+        "json.del",
+        "json.del",
+    ];
+    for event in events {
+        let msg = pubsub.get_message().expect("missing event");
+        assert_eq!(msg.get_payload::<String>().unwrap(), event);
+    }
+    pubsub
+        .get_message()
+        .expect_err("there should be no more events");
+}
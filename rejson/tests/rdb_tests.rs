@@ -0,0 +1,552 @@
+use common::{random_key, Ctx};
+use rand::Rng;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use test_context::test_context;
+
+mod common;
+
+// Just enough of the RDB format to write files holding module values of a single database, which
+// lets us feed the loader payloads written by other versions of the module.
+const RDB_OPCODE_EXPIRETIME_MS: u8 = 0xfc;
+const RDB_OPCODE_SELECTDB: u8 = 0xfe;
+const RDB_OPCODE_EOF: u8 = 0xff;
+const RDB_TYPE_MODULE_2: u8 = 7;
+const MODULE_OPCODE_EOF: u8 = 0;
+const MODULE_OPCODE_SINT: u8 = 1;
+const MODULE_OPCODE_UINT: u8 = 2;
+const MODULE_OPCODE_DOUBLE: u8 = 4;
+const MODULE_OPCODE_STRING: u8 = 5;
+
+enum Field {
+    Unsigned(u64),
+    Signed(i64),
+    Double(f64),
+    String(Vec<u8>),
+}
+
+fn rdb_len(n: u64, out: &mut Vec<u8>) {
+    if n < 1 << 6 {
+        out.push(n as u8);
+    } else if n < 1 << 14 {
+        out.extend_from_slice(&[((n >> 8) as u8) | 0x40, n as u8]);
+    } else if n <= u32::MAX as u64 {
+        out.push(0x80);
+        out.extend_from_slice(&(n as u32).to_be_bytes());
+    } else {
+        out.push(0x81);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+fn rdb_string(s: &[u8], out: &mut Vec<u8>) {
+    rdb_len(s.len() as u64, out);
+    out.extend_from_slice(s);
+}
+
+fn module_id(type_name: &str, encver: u64) -> u64 {
+    let charset = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let id = type_name
+        .chars()
+        .fold(0, |id, c| (id << 6) | charset.find(c).unwrap() as u64);
+    (id << 10) | encver
+}
+
+fn dump<K: AsRef<[u8]>>(type_name: &str, keys: &[(K, u64, Vec<Field>)]) -> Vec<u8> {
+    dump_with_expire(type_name, keys, None)
+}
+
+// Like dump but every key expires at the given unix time in milliseconds.
+fn dump_with_expire<K: AsRef<[u8]>>(
+    type_name: &str,
+    keys: &[(K, u64, Vec<Field>)],
+    expire: Option<u64>,
+) -> Vec<u8> {
+    let mut out = b"REDIS0009".to_vec();
+    out.push(RDB_OPCODE_SELECTDB);
+    rdb_len(0, &mut out);
+    for (key, encver, fields) in keys {
+        if let Some(ms) = expire {
+            out.push(RDB_OPCODE_EXPIRETIME_MS);
+            out.extend_from_slice(&ms.to_le_bytes());
+        }
+        out.push(RDB_TYPE_MODULE_2);
+        rdb_string(key.as_ref(), &mut out);
+        rdb_len(module_id(type_name, *encver), &mut out);
+        for field in fields {
+            match field {
+                Field::Unsigned(n) => {
+                    rdb_len(MODULE_OPCODE_UINT as u64, &mut out);
+                    rdb_len(*n, &mut out);
+                }
+                Field::Signed(n) => {
+                    rdb_len(MODULE_OPCODE_SINT as u64, &mut out);
+                    rdb_len(*n as u64, &mut out);
+                }
+                Field::Double(n) => {
+                    rdb_len(MODULE_OPCODE_DOUBLE as u64, &mut out);
+                    out.extend_from_slice(&n.to_le_bytes());
+                }
+                Field::String(s) => {
+                    rdb_len(MODULE_OPCODE_STRING as u64, &mut out);
+                    rdb_string(s, &mut out);
+                }
+            }
+        }
+        rdb_len(MODULE_OPCODE_EOF as u64, &mut out);
+    }
+    out.push(RDB_OPCODE_EOF);
+    // a checksum of zero disables its verification
+    out.extend_from_slice(&[0; 8]);
+    out
+}
+
+fn load(ctx: &mut Ctx, rdb: &[u8]) {
+    fs::write(ctx.dir().join("dump.rdb"), rdb).expect("writing dump.rdb failed");
+    ctx.restart(&[]);
+}
+
+fn json_get(con: &mut redis::Connection, key: &str) -> redis::Value {
+    redis::cmd("JSON.GET")
+        .arg(key)
+        .query::<redis::Value>(con)
+        .expect("json get failed")
+}
+
+#[test_context(Ctx)]
+#[test]
+fn save_and_load(ctx: &mut Ctx) {
+    let mut con = ctx.connection();
+
+    let documents = [
+        "null",
+        "true",
+        "false",
+        "0",
+        "-9223372036854775808",
+        "18446744073709551615",
+        "-2.5e-7",
+        r#""""#,
+        r#""a☺""#,
+        "[]",
+        "{}",
+        r#"{"b":[1,{"":null}],"a":{"c":"d"},"☺":[[],[1.5]]}"#,
+    ];
+    let keys = documents
+        .iter()
+        .map(|document| {
+            let key = random_key(16);
+            redis::cmd("JSON.SET")
+                .arg(&key)
+                .arg("$")
+                .arg(document)
+                .execute(&mut con);
+            key
+        })
+        .collect::<Vec<_>>();
+
+    redis::cmd("DEBUG").arg("RELOAD").execute(&mut con);
+
+    for (key, document) in keys.iter().zip(documents) {
+        assert_eq!(
+            json_get(&mut con, key),
+            redis::Value::Data(document.as_bytes().to_vec())
+        );
+    }
+}
+
+// Dumps saved by upstream releases with the documents below, see fixtures/generate.sh.
+fn upstream_dump(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "reading {} failed, save it with tests/fixtures/generate.sh: {e}",
+            path.display()
+        )
+    })
+}
+
+const UPSTREAM_DOCUMENTS: [(&str, &str); 3] = [
+    ("null", "null"),
+    ("scalars", r#"["a☺",2.5,-3,true,false,null]"#),
+    ("nested", r#"{"b":{},"a":[[]]}"#),
+];
+
+#[test_context(Ctx)]
+#[test]
+fn load_upstream_v1_dump(ctx: &mut Ctx) {
+    load(ctx, &upstream_dump("upstream-v1.rdb"));
+
+    let mut con = ctx.connection();
+    for (key, document) in UPSTREAM_DOCUMENTS {
+        assert_eq!(
+            json_get(&mut con, key),
+            redis::Value::Data(document.as_bytes().to_vec())
+        );
+    }
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_upstream_v2_dump(ctx: &mut Ctx) {
+    load(ctx, &upstream_dump("upstream-v2.rdb"));
+
+    let mut con = ctx.connection();
+    for (key, document) in UPSTREAM_DOCUMENTS {
+        assert_eq!(
+            json_get(&mut con, key),
+            redis::Value::Data(document.as_bytes().to_vec())
+        );
+    }
+}
+
+// Node types of the tree encoding of upstream's encoding version 0.
+const NODE_NULL: u64 = 0x1;
+const NODE_STRING: u64 = 0x2;
+const NODE_NUMBER: u64 = 0x4;
+const NODE_INTEGER: u64 = 0x8;
+const NODE_BOOLEAN: u64 = 0x10;
+const NODE_DICT: u64 = 0x20;
+const NODE_ARRAY: u64 = 0x40;
+const NODE_KEYVAL: u64 = 0x80;
+
+#[test_context(Ctx)]
+#[test]
+fn load_upstream_tree_encoding(ctx: &mut Ctx) {
+    load(
+        ctx,
+        &dump(
+            "ReJSON-RL",
+            &[
+                ("null", 0, vec![Field::Unsigned(NODE_NULL)]),
+                (
+                    "scalars",
+                    0,
+                    vec![
+                        Field::Unsigned(NODE_ARRAY),
+                        Field::Unsigned(6),
+                        Field::Unsigned(NODE_STRING),
+                        Field::String("a☺".as_bytes().to_vec()),
+                        Field::Unsigned(NODE_NUMBER),
+                        Field::Double(2.5),
+                        Field::Unsigned(NODE_INTEGER),
+                        Field::Signed(-3),
+                        Field::Unsigned(NODE_BOOLEAN),
+                        Field::String(b"1".to_vec()),
+                        Field::Unsigned(NODE_BOOLEAN),
+                        Field::String(b"0".to_vec()),
+                        Field::Unsigned(NODE_NULL),
+                    ],
+                ),
+                (
+                    "nested",
+                    0,
+                    vec![
+                        Field::Unsigned(NODE_DICT),
+                        Field::Unsigned(2),
+                        Field::Unsigned(NODE_KEYVAL),
+                        Field::String(b"b".to_vec()),
+                        Field::Unsigned(NODE_DICT),
+                        Field::Unsigned(0),
+                        Field::Unsigned(NODE_KEYVAL),
+                        Field::String(b"a".to_vec()),
+                        Field::Unsigned(NODE_ARRAY),
+                        Field::Unsigned(1),
+                        Field::Unsigned(NODE_ARRAY),
+                        Field::Unsigned(0),
+                    ],
+                ),
+            ],
+        ),
+    );
+
+    let mut con = ctx.connection();
+    for (key, document) in [
+        ("null", "null"),
+        ("scalars", r#"["a☺",2.5,-3,true,false,null]"#),
+        ("nested", r#"{"b":{},"a":[[]]}"#),
+    ] {
+        assert_eq!(
+            json_get(&mut con, key),
+            redis::Value::Data(document.as_bytes().to_vec())
+        );
+    }
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_upstream_string_encodings(ctx: &mut Ctx) {
+    let document = r#"{"a":[1,2.5,"b"],"c":null}"#;
+
+    load(
+        ctx,
+        &dump(
+            "ReJSON-RL",
+            &[
+                (
+                    "v2",
+                    2,
+                    vec![
+                        Field::String(document.as_bytes().to_vec()),
+                        Field::Unsigned(0),
+                    ],
+                ),
+                (
+                    "v2-search",
+                    2,
+                    vec![
+                        Field::String(document.as_bytes().to_vec()),
+                        Field::Unsigned(1),
+                        Field::String(b"index".to_vec()),
+                        Field::String(b"field".to_vec()),
+                    ],
+                ),
+                ("v3", 3, vec![Field::String(document.as_bytes().to_vec())]),
+            ],
+        ),
+    );
+
+    let mut con = ctx.connection();
+    for key in ["v2", "v2-search", "v3"] {
+        assert_eq!(
+            json_get(&mut con, key),
+            redis::Value::Data(document.as_bytes().to_vec())
+        );
+    }
+}
+
+// {"a":[1,"b"]} in the binary encoding of version 4
+const ENCODED_DOCUMENT: &[u8] = &[8, 1, 1, b'a', 7, 2, 3, 1, 0, 0, 0, 0, 0, 0, 0, 6, 1, b'b'];
+
+#[test_context(Ctx)]
+#[test]
+fn load_legacy_type_name(ctx: &mut Ctx) {
+    load(
+        ctx,
+        &dump(
+            "RedisJSON",
+            &[
+                (
+                    "v0",
+                    0,
+                    vec![Field::String(r#"{"a":[1,"b"]}"#.as_bytes().to_vec())],
+                ),
+                ("v4", 4, vec![Field::String(ENCODED_DOCUMENT.to_vec())]),
+            ],
+        ),
+    );
+
+    // the keys are converted to the current type and can be written like any other
+    let mut con = ctx.connection();
+    for key in ["v0", "v4"] {
+        assert_eq!(
+            redis::cmd("TYPE")
+                .arg(key)
+                .query::<String>(&mut con)
+                .expect("type failed"),
+            "ReJSON-RL"
+        );
+        redis::cmd("JSON.ARRAPPEND")
+            .arg(key)
+            .arg("$.a")
+            .arg("2")
+            .execute(&mut con);
+        assert_eq!(
+            json_get(&mut con, key),
+            redis::Value::Data(r#"{"a":[1,"b",2]}"#.as_bytes().to_vec())
+        );
+    }
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_legacy_type_name_binary_key(ctx: &mut Ctx) {
+    let key: &[u8] = b"\xff\x00k";
+    load(
+        ctx,
+        &dump(
+            "RedisJSON",
+            &[(key, 4, vec![Field::String(ENCODED_DOCUMENT.to_vec())])],
+        ),
+    );
+
+    let mut con = ctx.connection();
+    assert_eq!(
+        redis::cmd("TYPE")
+            .arg(key)
+            .query::<String>(&mut con)
+            .expect("type failed"),
+        "ReJSON-RL"
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_legacy_type_name_keeps_expire(ctx: &mut Ctx) {
+    let in_an_hour = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+        + 3600 * 1000;
+    load(
+        ctx,
+        &dump_with_expire(
+            "RedisJSON",
+            &[("v4", 4, vec![Field::String(ENCODED_DOCUMENT.to_vec())])],
+            Some(in_an_hour),
+        ),
+    );
+
+    let mut con = ctx.connection();
+    assert_eq!(
+        redis::cmd("TYPE")
+            .arg("v4")
+            .query::<String>(&mut con)
+            .expect("type failed"),
+        "ReJSON-RL"
+    );
+    let ttl = redis::cmd("TTL")
+        .arg("v4")
+        .query::<i64>(&mut con)
+        .expect("ttl failed");
+    assert!(ttl > 3500 && ttl <= 3600, "{ttl}");
+}
+
+fn corrupted_dump() -> Vec<u8> {
+    dump(
+        "ReJSON-RL",
+        &[
+            ("valid", 4, vec![Field::String(ENCODED_DOCUMENT.to_vec())]),
+            (
+                "truncated",
+                4,
+                vec![Field::String(ENCODED_DOCUMENT[..10].to_vec())],
+            ),
+        ],
+    )
+}
+
+fn log_file(ctx: &Ctx) -> String {
+    ctx.dir().join("redis.log").display().to_string()
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_error_fails_load(ctx: &mut Ctx) {
+    fs::write(ctx.dir().join("dump.rdb"), corrupted_dump()).expect("writing dump.rdb failed");
+    let log = log_file(ctx);
+
+    ctx.try_restart(&[], &[&format!("--logfile {log}")])
+        .expect_err("redis should have failed loading");
+
+    let log = fs::read_to_string(log).expect("reading redis.log failed");
+    assert!(log.contains("failed to load key 'truncated': unexpected end of data at offset 7"));
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_error_skips_key(ctx: &mut Ctx) {
+    fs::write(ctx.dir().join("dump.rdb"), corrupted_dump()).expect("writing dump.rdb failed");
+    let log = log_file(ctx);
+
+    ctx.try_restart(&["RDB_LOAD_ERROR", "skip"], &[&format!("--logfile {log}")])
+        .expect("redis exited during startup");
+
+    let mut con = ctx.connection();
+    assert_eq!(
+        json_get(&mut con, "valid"),
+        redis::Value::Data(r#"{"a":[1,"b"]}"#.as_bytes().to_vec())
+    );
+    assert_eq!(json_get(&mut con, "truncated"), redis::Value::Nil);
+
+    let log = fs::read_to_string(log).expect("reading redis.log failed");
+    assert!(log.contains("skipping key 'truncated': unexpected end of data at offset 7"));
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_error_skips_binary_key(ctx: &mut Ctx) {
+    let key: &[u8] = b"\xff\x00k";
+    fs::write(
+        ctx.dir().join("dump.rdb"),
+        dump(
+            "ReJSON-RL",
+            &[(key, 4, vec![Field::String(ENCODED_DOCUMENT[..10].to_vec())])],
+        ),
+    )
+    .expect("writing dump.rdb failed");
+
+    ctx.try_restart(&["RDB_LOAD_ERROR", "skip"], &[])
+        .expect("redis exited during startup");
+
+    let mut con = ctx.connection();
+    assert_eq!(
+        redis::cmd("EXISTS")
+            .arg(key)
+            .query::<redis::Value>(&mut con)
+            .expect("exists failed"),
+        redis::Value::Int(0)
+    );
+}
+
+#[test_context(Ctx)]
+#[test]
+fn invalid_module_arguments(ctx: &mut Ctx) {
+    ctx.try_restart(&["RDB_LOAD_ERROR", "ignore"], &[])
+        .expect_err("redis should have failed loading the module");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn load_corrupted_payloads(ctx: &mut Ctx) {
+    let mut rng = rand::thread_rng();
+
+    let mut payloads = vec![
+        // nested deeper than the loader accepts
+        [7, 1].repeat(100000),
+        // invalid utf-8
+        vec![6, 2, 0xc3, 0x28],
+        // trailing data
+        [ENCODED_DOCUMENT, &[0]].concat(),
+        // a length that doesn't fit into 64 bits
+        [&[7][..], &[0xff; 10], &[1]].concat(),
+    ];
+    for i in 0..ENCODED_DOCUMENT.len() {
+        payloads.push(ENCODED_DOCUMENT[..i].to_vec());
+        let mut payload = ENCODED_DOCUMENT.to_vec();
+        payload[i] = rng.gen();
+        payloads.push(payload);
+    }
+    for _ in 0..100 {
+        let len = rng.gen_range(0..32);
+        payloads.push((0..len).map(|_| rng.gen_range(0..12)).collect());
+    }
+
+    let keys = (0..payloads.len())
+        .map(|i| format!("key-{i}"))
+        .collect::<Vec<_>>();
+    let mut entries = keys
+        .iter()
+        .zip(payloads)
+        .map(|(key, payload)| (key.as_str(), 4, vec![Field::String(payload)]))
+        .collect::<Vec<_>>();
+    entries.push(("valid", 4, vec![Field::String(ENCODED_DOCUMENT.to_vec())]));
+    fs::write(ctx.dir().join("dump.rdb"), dump("ReJSON-RL", &entries))
+        .expect("writing dump.rdb failed");
+
+    ctx.try_restart(&["RDB_LOAD_ERROR", "skip"], &[])
+        .expect("redis exited during startup");
+
+    // corrupted payloads may still decode to some document, but the server must survive them
+    let mut con = ctx.connection();
+    assert_eq!(
+        json_get(&mut con, "valid"),
+        redis::Value::Data(r#"{"a":[1,"b"]}"#.as_bytes().to_vec())
+    );
+    for key in keys {
+        json_get(&mut con, &key);
+    }
+}
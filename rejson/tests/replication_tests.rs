@@ -0,0 +1,110 @@
+use common::{random_key, Ctx};
+use std::{thread, time};
+use test_context::{test_context, TestContext};
+
+mod common;
+
+fn wait_for_replication(con: &mut redis::Connection) {
+    for _ in 0..100 {
+        let info = redis::cmd("INFO")
+            .arg("replication")
+            .query::<String>(con)
+            .unwrap();
+        if info.contains("master_link_status:up") {
+            return;
+        }
+        thread::sleep(time::Duration::from_millis(100));
+    }
+    panic!("replica did not connect to its master");
+}
+
+#[test_context(Ctx)]
+#[test]
+fn replica_follows_writes(ctx: &mut Ctx) {
+    let mut replica = Ctx::setup();
+
+    let mut con = ctx.connection();
+    let mut replica_con = replica.connection();
+
+    redis::cmd("REPLICAOF")
+        .arg("127.0.0.1")
+        .arg(ctx.port())
+        .execute(&mut replica_con);
+    wait_for_replication(&mut replica_con);
+
+    let key = random_key(16);
+    let other = random_key(16);
+    let missing = random_key(16);
+
+    let commands: &[&[&str]] = &[
+        &[
+            "JSON.SET",
+            &key,
+            "$",
+            r#"{"a":[1,2,3],"b":"c","d":1,"e":true}"#,
+        ],
+        &["JSON.SET", &key, "$.f", "{}", "NX"],
+        &["JSON.SET", &key, "$.f", "[]", "XX"],
+        &["JSON.SET", &other, "$", "{}", "NX"],
+        &["JSON.SET", &other, "$.g.h", "1", "XX", "CREATE"],
+        &["JSON.SET", &missing, "$", "{}", "XX"],
+        &["JSON.ARRAPPEND", &key, "$.a", "4"],
+        &["JSON.ARRINSERT", &key, "$.a", "0", "0"],
+        &["JSON.ARRPOP", &key, "$.a", "-2"],
+        &["JSON.ARRTRIM", &key, "$.a", "1", "-1"],
+        &["JSON.STRAPPEND", &key, "$.b", r#""d""#],
+        &["JSON.NUMINCRBY", &key, "$.d", "0.1"],
+        &["JSON.NUMMULTBY", &key, "$.d", "3"],
+        &["JSON.TOGGLE", &key, "$.e"],
+        &["JSON.MERGE", &key, "$", r#"{"i":{"j":null},"k":1}"#],
+        &[
+            "JSON.PATCH",
+            &key,
+            r#"[{"op":"move","from":"/k","path":"/l"}]"#,
+        ],
+        &["JSON.MSET", &key, "$.m", "1", &other, "$.n", "2"],
+        &["JSON.CLEAR", &key, "$.i"],
+        &["JSON.DEL", &key, "$.m"],
+        &["JSON.FORGET", &other, "$.g"],
+    ];
+    for command in commands {
+        redis::cmd(command[0])
+            .arg(&command[1..])
+            .query::<redis::Value>(&mut con)
+            .unwrap_or_else(|e| panic!("{} failed: {e}", command[0]));
+    }
+
+    assert_eq!(
+        redis::cmd("WAIT")
+            .arg(1)
+            .arg(5000)
+            .query::<i64>(&mut con)
+            .expect("wait failed"),
+        1
+    );
+
+    for k in [&key, &other, &missing] {
+        assert_eq!(
+            redis::cmd("JSON.GET")
+                .arg(k)
+                .query::<redis::Value>(&mut replica_con)
+                .expect("json get failed"),
+            redis::cmd("JSON.GET")
+                .arg(k)
+                .query::<redis::Value>(&mut con)
+                .expect("json get failed"),
+        );
+    }
+    assert_eq!(
+        redis::cmd("DEBUG")
+            .arg("DIGEST")
+            .query::<String>(&mut replica_con)
+            .expect("debug digest failed"),
+        redis::cmd("DEBUG")
+            .arg("DIGEST")
+            .query::<String>(&mut con)
+            .expect("debug digest failed"),
+    );
+
+    replica.teardown();
+}